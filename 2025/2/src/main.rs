@@ -1,5 +1,7 @@
 use std::ops::RangeInclusive;
 
+use common::RangeSet;
+
 const EXAMPLE: bool = false;
 const INPUT_PATH: &str = if EXAMPLE { "example" } else { "input" };
 
@@ -26,7 +28,8 @@ fn main() {
     let file = std::fs::read_to_string(INPUT_PATH)
         .expect("INPUT_PATH should contain the path of the input file");
 
-    let ranges = parse_ranges(&file);
+    // Combining the ranges into a RangeSet ensures no ID is checked twice
+    let ranges: RangeSet<u64> = parse_ranges(&file).into_iter().collect();
 
     // Simple invalid IDs are those formed from some sequence repeated twice.
     let mut simple_invalid_ids_sum = 0;
@@ -34,7 +37,7 @@ fn main() {
     // All invalid IDs are formed from some sequence repeated any number of times.
     let mut all_invalid_ids_sum = 0;
 
-    for range in ranges {
+    for range in ranges.iter().cloned() {
         for id in range {
             let id_str = id.to_string();
             let id_len = id_str.len();
@@ -0,0 +1,153 @@
+use std::{fs::File, io::Write, path::Path};
+
+// Each grid position is drawn as a square of this many pixels in PNG frames
+const PNG_SCALE: usize = 4;
+
+/// What is drawn at one position of a frame
+#[derive(Clone, Copy)]
+pub enum FrameTile {
+    Empty,
+    Paper,
+    /// Paper which was removed in the round this frame shows
+    Removed,
+}
+
+impl FrameTile {
+    /// The RGB colour used for this tile in PNG frames
+    fn colour(&self) -> [u8; 3] {
+        match self {
+            FrameTile::Empty => [24, 24, 32],
+            FrameTile::Paper => [230, 230, 220],
+            FrameTile::Removed => [220, 50, 50],
+        }
+    }
+
+    /// The ANSI escape sequence and character used for this tile in the terminal
+    fn ansi(&self) -> &'static str {
+        match self {
+            FrameTile::Empty => "\x1b[90m.",
+            FrameTile::Paper => "\x1b[97m@",
+            FrameTile::Removed => "\x1b[91m@",
+        }
+    }
+}
+
+/// Clear the terminal and draw the frame in its place, followed by a caption
+pub fn print_ansi(frame: &[Vec<FrameTile>], caption: &str) {
+    let mut output = String::from("\x1b[2J\x1b[H");
+
+    for row in frame {
+        for tile in row {
+            output.push_str(tile.ansi());
+        }
+        output.push_str("\x1b[0m\n");
+    }
+
+    output.push_str(caption);
+
+    println!("{output}");
+}
+
+/// Write the frame to the given path as an RGB PNG image
+///
+/// The image data is stored uncompressed, so no compression library is needed.
+pub fn write_png(frame: &[Vec<FrameTile>], path: &Path) -> std::io::Result<()> {
+    let height = frame.len() * PNG_SCALE;
+    let width = frame.iter().map(|row| row.len()).max().unwrap_or(0) * PNG_SCALE;
+
+    // Each scanline starts with a filter type byte, where 0 means no filtering.
+    // Rows shorter than the widest row are padded with empty tiles.
+    let mut scanlines = Vec::with_capacity(height * (1 + 3 * width));
+    for row in frame {
+        let mut scanline = vec![0];
+        for col in 0..width / PNG_SCALE {
+            let colour = row.get(col).unwrap_or(&FrameTile::Empty).colour();
+            for _ in 0..PNG_SCALE {
+                scanline.extend_from_slice(&colour);
+            }
+        }
+
+        for _ in 0..PNG_SCALE {
+            scanlines.extend_from_slice(&scanline);
+        }
+    }
+
+    let mut header = vec![];
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // Bit depth 8, colour type 2 (RGB), then default compression, filtering and no interlacing
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut file = File::create(path)?;
+    file.write_all(b"\x89PNG\r\n\x1a\n")?;
+    write_chunk(&mut file, b"IHDR", &header)?;
+    write_chunk(&mut file, b"IDAT", &zlib_stored(&scanlines))?;
+    write_chunk(&mut file, b"IEND", &[])
+}
+
+/// Write one PNG chunk: its length, type, data and a CRC of the type and data
+fn write_chunk(file: &mut File, chunk_type: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    file.write_all(&(data.len() as u32).to_be_bytes())?;
+    file.write_all(chunk_type)?;
+    file.write_all(data)?;
+
+    let crc = crc32(chunk_type.iter().chain(data));
+    file.write_all(&crc.to_be_bytes())
+}
+
+/// Wrap the data in a zlib stream made of uncompressed ("stored") deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate compression method with a 32K window, and no preset dictionary
+    let mut stream = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+
+    // An empty stream still needs one (final) block
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        stream.push(is_final as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+
+    stream
+}
+
+/// The CRC-32 checksum used by PNG chunks
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = u32::MAX;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// The Adler-32 checksum ending a zlib stream
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
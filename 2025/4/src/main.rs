@@ -1,17 +1,394 @@
+mod frames;
+
+use std::path::PathBuf;
+
+use frames::FrameTile;
+
 const EXAMPLE: bool = false;
 const INPUT_PATH: &str = if EXAMPLE { "example" } else { "input" };
 const PAPER_CHAR: char = '@';
+const ANIMATION_FRAME_MILLIS: u64 = 250;
+const HISTOGRAM_WIDTH: usize = 50;
+
+/// The set of positions, relative to a tile, which count as that tile's neighbours
+#[derive(Clone)]
+enum Neighbourhood {
+    /// The 4 orthogonally adjacent positions
+    VonNeumann,
+    /// The 8 orthogonally or diagonally adjacent positions
+    Moore,
+    /// Every position within the given number of rows and columns, i.e. a (2r+1)x(2r+1) square
+    Radius(usize),
+    /// An arbitrary list of (row_offset, col_offset) pairs
+    Custom(Vec<(isize, isize)>),
+}
+
+// Parse a neighbourhood from one of "von-neumann", "moore", "radius-<r>",
+// or "custom:<row_offset>,<col_offset>;<row_offset>,<col_offset>;..."
+impl TryFrom<&str> for Neighbourhood {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if let Some(radius_str) = value.strip_prefix("radius-") {
+            let radius = radius_str.parse().map_err(|_| "Could not parse radius!")?;
+
+            Ok(Neighbourhood::Radius(radius))
+        } else if let Some(offsets_str) = value.strip_prefix("custom:") {
+            let mut offsets = vec![];
+
+            for offset_str in offsets_str.split(";") {
+                let (row_str, col_str) = offset_str
+                    .split_once(",")
+                    .ok_or("Each custom offset should be of the form row,col!")?;
+
+                let row_offset = row_str
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Could not parse row offset!")?;
+                let col_offset = col_str
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Could not parse col offset!")?;
+
+                offsets.push((row_offset, col_offset));
+            }
+
+            Ok(Neighbourhood::Custom(offsets))
+        } else {
+            match value {
+                "von-neumann" => Ok(Neighbourhood::VonNeumann),
+                "moore" => Ok(Neighbourhood::Moore),
+                _ => Err("Only von-neumann, moore, radius-<r> and custom:<offsets> are supported!"),
+            }
+        }
+    }
+}
+
+impl Neighbourhood {
+    /// Get the (row_offset, col_offset) pairs making up the neighbourhood
+    ///
+    /// Apart from in a Custom neighbourhood, a tile is not considered its own neighbour.
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Neighbourhood::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => Neighbourhood::Radius(1).offsets(),
+            Neighbourhood::Radius(radius) => {
+                let radius = *radius as isize;
+                let mut offsets = vec![];
+
+                for row_offset in -radius..=radius {
+                    for col_offset in -radius..=radius {
+                        if row_offset != 0 || col_offset != 0 {
+                            offsets.push((row_offset, col_offset));
+                        }
+                    }
+                }
+
+                offsets
+            }
+            Neighbourhood::Custom(offsets) => offsets.clone(),
+        }
+    }
+}
+
+/// How the number of paper neighbours is compared against the threshold
+#[derive(Clone, Copy)]
+enum Comparison {
+    LessThan,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    GreaterThan,
+}
+
+impl TryFrom<&str> for Comparison {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "<" => Ok(Comparison::LessThan),
+            "<=" => Ok(Comparison::LessOrEqual),
+            "==" => Ok(Comparison::Equal),
+            ">=" => Ok(Comparison::GreaterOrEqual),
+            ">" => Ok(Comparison::GreaterThan),
+            _ => Err("Only <, <=, ==, >= and > are supported comparisons!"),
+        }
+    }
+}
+
+/// How positions beyond the edges of the grid are treated when counting neighbours
+#[derive(Clone, Copy)]
+enum EdgeMode {
+    /// Positions out of bounds are never paper
+    Wall,
+    /// The grid wraps around at its edges, forming a torus
+    Torus,
+    /// Positions out of bounds are always paper, and can never be removed
+    Paper,
+}
+
+impl TryFrom<&str> for EdgeMode {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "wall" => Ok(EdgeMode::Wall),
+            "torus" => Ok(EdgeMode::Torus),
+            "paper" => Ok(EdgeMode::Paper),
+            _ => Err("Only wall, torus and paper are supported edge modes!"),
+        }
+    }
+}
+
+/// The rule deciding whether a roll of paper is accessible
+#[derive(Clone)]
+struct Rule {
+    neighbourhood: Neighbourhood,
+    comparison: Comparison,
+    threshold: usize,
+    edge_mode: EdgeMode,
+}
+
+impl Rule {
+    /// The rule given by the puzzle: fewer than 4 paper rolls among the 8 surrounding positions
+    fn puzzle() -> Rule {
+        Rule {
+            neighbourhood: Neighbourhood::Moore,
+            comparison: Comparison::LessThan,
+            threshold: 4,
+            edge_mode: EdgeMode::Wall,
+        }
+    }
+
+    /// Return true if and only if a tile with this many paper neighbours is accessible
+    fn allows(&self, paper_neighbours: usize) -> bool {
+        match self.comparison {
+            Comparison::LessThan => paper_neighbours < self.threshold,
+            Comparison::LessOrEqual => paper_neighbours <= self.threshold,
+            Comparison::Equal => paper_neighbours == self.threshold,
+            Comparison::GreaterOrEqual => paper_neighbours >= self.threshold,
+            Comparison::GreaterThan => paper_neighbours > self.threshold,
+        }
+    }
+}
+
+/// The options which can be given on the command line
+struct Options {
+    rule: Rule,
+    // Whether to animate each removal round in the terminal
+    animate: bool,
+    // The directory to write each removal round to as a PNG frame, if any
+    png_dir: Option<PathBuf>,
+    // Whether to print the round in which each roll of paper was removed
+    depth_map: bool,
+    // Whether to print how many rolls of paper were removed in each round
+    depth_histogram: bool,
+}
+
+impl Options {
+    /// Build the options from command line arguments, starting from the puzzle's rule.
+    ///
+    /// The rule is changed with --neighbourhood, --comparison, --threshold and --edge-mode,
+    /// each followed by its value, e.g. --neighbourhood von-neumann --comparison "<=".
+    /// --animate plays the removal rounds in the terminal, and --png <dir> writes them as frames.
+    /// --depth-map and --depth-histogram print when each roll of paper was removed.
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, &'static str> {
+        let mut options = Options {
+            rule: Rule::puzzle(),
+            animate: false,
+            png_dir: None,
+            depth_map: false,
+            depth_histogram: false,
+        };
+
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                // These flags do not take a value
+                "--animate" => options.animate = true,
+                "--depth-map" => options.depth_map = true,
+                "--depth-histogram" => options.depth_histogram = true,
+                _ => {
+                    let value = args
+                        .next()
+                        .ok_or("Each argument should be followed by a value!")?;
+                    let rule = &mut options.rule;
+
+                    match flag.as_str() {
+                        "--neighbourhood" => {
+                            rule.neighbourhood = Neighbourhood::try_from(value.as_str())?
+                        }
+                        "--comparison" => rule.comparison = Comparison::try_from(value.as_str())?,
+                        "--threshold" => {
+                            rule.threshold =
+                                value.parse().map_err(|_| "Could not parse threshold!")?
+                        }
+                        "--edge-mode" => rule.edge_mode = EdgeMode::try_from(value.as_str())?,
+                        "--png" => options.png_dir = Some(PathBuf::from(value)),
+                        _ => return Err("Unrecognised argument!"),
+                    }
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// When a position of the grid had its paper removed
+#[derive(Clone, Copy, PartialEq)]
+enum Depth {
+    /// The position never held paper
+    Empty,
+    /// The paper was removed in the given round, counting from 1
+    Removed(usize),
+    /// The paper was never removed
+    Never,
+}
+
+/// The round in which each roll of paper was removed, giving the order the grid is peeled in
+struct DepthMap(Vec<Vec<Depth>>);
+
+impl DepthMap {
+    /// Create a depth map of the grid before anything is removed,
+    /// where all the paper is assumed to never be removed until recorded otherwise
+    fn new(grid: &Grid) -> DepthMap {
+        DepthMap(
+            grid.tiles
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|tile| match tile {
+                            GridTile::Empty => Depth::Empty,
+                            GridTile::Paper => Depth::Never,
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Record the paper removed by the grid's most recent round, which was the given round
+    fn record_round(&mut self, grid: &Grid, round: usize) {
+        for &(row, col) in &grid.last_removed {
+            self.0[row][col] = Depth::Removed(round);
+        }
+    }
+
+    /// Count the paper removed in each round, in order of round, followed by the paper never removed
+    fn histogram(&self) -> Vec<(Depth, usize)> {
+        let mut removed_counts = vec![];
+        let mut never_count = 0;
+
+        for depth in self.0.iter().flatten() {
+            match depth {
+                Depth::Empty => (),
+                Depth::Removed(round) => {
+                    if removed_counts.len() < *round {
+                        removed_counts.resize(*round, 0);
+                    }
+                    removed_counts[round - 1] += 1;
+                }
+                Depth::Never => never_count += 1,
+            }
+        }
+
+        removed_counts
+            .into_iter()
+            .enumerate()
+            .map(|(index, count)| (Depth::Removed(index + 1), count))
+            .chain([(Depth::Never, never_count)])
+            .collect()
+    }
+
+    /// Print the histogram, with a bar scaled so that the largest count fills HISTOGRAM_WIDTH
+    fn print_histogram(&self) {
+        let histogram = self.histogram();
+        let max_count = histogram.iter().map(|&(_, count)| count).max().unwrap_or(0);
+
+        for (depth, count) in histogram {
+            let label = match depth {
+                Depth::Removed(round) => format!("Round {round}"),
+                _ => String::from("Never"),
+            };
+
+            let bar_len = (count * HISTOGRAM_WIDTH).div_ceil(max_count.max(1));
+
+            println!("{label:>9}: {count:6} {}", "#".repeat(bar_len));
+        }
+    }
+}
+
+// Display the depth map as a grid of round numbers, padded to the same width and separated by spaces.
+// Positions which never held paper are shown as '.', and paper which was never removed as '#'.
+impl std::fmt::Display for DepthMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let max_round = self
+            .0
+            .iter()
+            .flatten()
+            .filter_map(|depth| match depth {
+                Depth::Removed(round) => Some(*round),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let width = max_round.to_string().len();
+
+        for row in &self.0 {
+            for (col, depth) in row.iter().enumerate() {
+                if col != 0 {
+                    write!(f, " ")?;
+                }
+
+                match depth {
+                    Depth::Empty => write!(f, "{:>width$}", '.')?,
+                    Depth::Removed(round) => write!(f, "{round:>width$}")?,
+                    Depth::Never => write!(f, "{:>width$}", '#')?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Where a neighbour offset from some position lands
+enum Landing {
+    /// A position within the grid
+    Inside(usize, usize),
+    /// Beyond the edge of the grid, when the EdgeMode does not wrap around
+    Outside,
+}
 
 enum GridTile {
     Empty,
     Paper,
 }
 
-struct Grid(Vec<Vec<GridTile>>);
+struct Grid {
+    tiles: Vec<Vec<GridTile>>,
+    rule: Rule,
+    offsets: Vec<(isize, isize)>,
+    // The number of paper neighbours of every position, kept up to date as paper is removed
+    paper_neighbours: Vec<Vec<usize>>,
+    // Whether a position has already been put on the accessible worklist
+    queued: Vec<Vec<bool>>,
+    // The worklist of paper positions which are accessible, and will be removed next round
+    accessible: Vec<(usize, usize)>,
+    // The positions removed in the most recent round
+    last_removed: Vec<(usize, usize)>,
+}
 
 impl Grid {
-    fn from_file(file: &str) -> Grid {
-        let mut grid = vec![];
+    /// Read the grid from the file, applying the given rule
+    ///
+    /// Shorter lines are padded with empty positions to the width of the longest, so the grid is
+    /// always a rectangle. Otherwise wrapping around a torus would depend on the width of the row
+    /// landed on, and moving back by the same offset would not return to where it started.
+    fn from_file(file: &str, rule: Rule) -> Grid {
+        let mut tiles = vec![];
 
         for line in file.lines() {
             let mut row_vec = vec![];
@@ -24,10 +401,50 @@ impl Grid {
                 }
             }
 
-            grid.push(row_vec);
+            tiles.push(row_vec);
         }
 
-        Grid(grid)
+        let width = tiles.iter().map(Vec::len).max().unwrap_or(0);
+        for row_vec in &mut tiles {
+            row_vec.resize_with(width, || GridTile::Empty);
+        }
+
+        let paper_neighbours = tiles.iter().map(|row| vec![0; row.len()]).collect();
+        let queued = tiles.iter().map(|row| vec![false; row.len()]).collect();
+
+        let mut grid = Grid {
+            tiles,
+            offsets: rule.neighbourhood.offsets(),
+            rule,
+            paper_neighbours,
+            queued,
+            accessible: vec![],
+            last_removed: vec![],
+        };
+
+        // Count the paper neighbours of every position once up front.
+        // From here on, the counts are only ever decremented as paper is removed.
+        for row in 0..grid.tiles.len() {
+            for col in 0..grid.tiles[row].len() {
+                grid.paper_neighbours[row][col] = grid
+                    .offsets
+                    .iter()
+                    .filter(|&&offset| match grid.land(row, col, offset, 1) {
+                        Landing::Inside(row, col) => grid.is_paper_at(row as isize, col as isize),
+                        Landing::Outside => matches!(grid.rule.edge_mode, EdgeMode::Paper),
+                    })
+                    .count();
+            }
+        }
+
+        // Prime the worklist with every position which is accessible from the start
+        for row in 0..grid.tiles.len() {
+            for col in 0..grid.tiles[row].len() {
+                grid.queue_if_accessible(row, col);
+            }
+        }
+
+        grid
     }
 
     // Return true if and only if the grid contains a GridTile::Paper at the given row and col
@@ -35,7 +452,7 @@ impl Grid {
     fn is_paper_at(&self, row: isize, col: isize) -> bool {
         if row < 0 || col < 0 {
             false
-        } else if let Some(row_vec) = self.0.get(row as usize)
+        } else if let Some(row_vec) = self.tiles.get(row as usize)
             && let Some(value) = row_vec.get(col as usize)
         {
             matches!(value, GridTile::Paper)
@@ -44,53 +461,141 @@ impl Grid {
         }
     }
 
-    // Return true if and only if the given row and column has fewer than 4 paper neighbours,
-    // among the 8 possible neighbouring positions.
-    fn is_accessible(&self, row: usize, col: usize) -> bool {
-        let mut paper_neighbours = 0u8;
+    // Find where moving by the given offset, scaled by direction (1 or -1), lands from a position.
+    // Whether it lands outside the grid or wraps around is decided by the rule's EdgeMode.
+    fn land(&self, row: usize, col: usize, offset: (isize, isize), direction: isize) -> Landing {
+        let height = self.tiles.len() as isize;
+        let mut target_row = row as isize + direction * offset.0;
 
-        for row_offset in [-1, 0, 1] {
-            for col_offset in [-1, 0, 1] {
-                // Skip checking the position itself (a tile is not considered its own neighbour)
-                if row_offset == 0 && col_offset == 0 {
-                    continue;
-                }
+        if matches!(self.rule.edge_mode, EdgeMode::Torus) {
+            target_row = target_row.rem_euclid(height);
+        } else if target_row < 0 || target_row >= height {
+            return Landing::Outside;
+        }
 
-                if self.is_paper_at(row as isize + row_offset, col as isize + col_offset) {
-                    paper_neighbours += 1;
-                }
+        let width = self.tiles[target_row as usize].len() as isize;
+        let mut target_col = col as isize + direction * offset.1;
+
+        if matches!(self.rule.edge_mode, EdgeMode::Torus) {
+            if width == 0 {
+                return Landing::Outside;
             }
+            target_col = target_col.rem_euclid(width);
+        } else if target_col < 0 || target_col >= width {
+            return Landing::Outside;
         }
 
-        paper_neighbours < 4
+        Landing::Inside(target_row as usize, target_col as usize)
+    }
+
+    // Return every position which counts the given position as one of its neighbours.
+    // A position appears once for each of its offsets which lands on the given position.
+    fn neighbours_of(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.offsets
+            .iter()
+            .filter_map(|&offset| match self.land(row, col, offset, -1) {
+                Landing::Inside(row, col) => Some((row, col)),
+                Landing::Outside => None,
+            })
+            .collect()
+    }
+
+    // Return true if and only if the number of paper neighbours of the given row and column
+    // satisfies the rule, e.g. fewer than 4 among the 8 possible neighbouring positions.
+    fn is_accessible(&self, row: usize, col: usize) -> bool {
+        self.rule.allows(self.paper_neighbours[row][col])
+    }
+
+    // Put the given position on the worklist if it holds accessible paper and is not already on it
+    fn queue_if_accessible(&mut self, row: usize, col: usize) {
+        if !self.queued[row][col]
+            && self.is_paper_at(row as isize, col as isize)
+            && self.is_accessible(row, col)
+        {
+            self.queued[row][col] = true;
+            self.accessible.push((row, col));
+        }
     }
 
-    // Set each *currently* accessible position in the grid to GridTile::Empty.
+    // Set each *currently* accessible position in the grid to GridTile::Empty,
+    // and return how many were removed.
     // The tiles are only removed at the end, so removal of earlier tiles will not affect the
     // removal of later tiles.
-    fn remove_acccessible(&mut self) {
-        let mut accessible_positions = vec![];
+    //
+    // Only the neighbours of removed tiles can have their accessibility changed by a removal,
+    // so they are the only positions re-examined to build the worklist for the next round.
+    // They are only re-examined once all the counts are updated, since a rule need not be
+    // monotonic in the number of paper neighbours.
+    fn remove_acccessible(&mut self) -> usize {
+        let removed_positions = std::mem::take(&mut self.accessible);
 
-        for row in 0..self.0.len() {
-            for col in 0..self.0[row].len() {
-                if matches!(self.0[row][col], GridTile::Paper) && self.is_accessible(row, col) {
-                    accessible_positions.push((row, col));
-                }
+        for &(row, col) in &removed_positions {
+            self.tiles[row][col] = GridTile::Empty;
+        }
+
+        let mut affected_positions = vec![];
+
+        for &(row, col) in &removed_positions {
+            for (neighbour_row, neighbour_col) in self.neighbours_of(row, col) {
+                self.paper_neighbours[neighbour_row][neighbour_col] -= 1;
+                affected_positions.push((neighbour_row, neighbour_col));
             }
         }
 
-        for (row, col) in accessible_positions {
-            self.0[row][col] = GridTile::Empty;
+        for (row, col) in affected_positions {
+            self.queue_if_accessible(row, col);
         }
+
+        self.last_removed = removed_positions;
+
+        self.last_removed.len()
     }
 
-    // Count the number of GridTile::Paper on the grid
-    fn count_paper(&self) -> usize {
-        self.0
+    // Get the current state of the grid as a frame for visualisation,
+    // with the paper removed in the most recent round marked as such.
+    fn frame(&self) -> Vec<Vec<FrameTile>> {
+        let mut frame: Vec<Vec<FrameTile>> = self
+            .tiles
             .iter()
-            .flatten()
-            .filter(|tile| matches!(tile, GridTile::Paper))
-            .count()
+            .map(|row| {
+                row.iter()
+                    .map(|tile| match tile {
+                        GridTile::Empty => FrameTile::Empty,
+                        GridTile::Paper => FrameTile::Paper,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for &(row, col) in &self.last_removed {
+            frame[row][col] = FrameTile::Removed;
+        }
+
+        frame
+    }
+
+    // Show the current frame in the terminal and/or write it as a PNG, depending on the options
+    fn export_frame(&self, options: &Options, round: usize) {
+        if !options.animate && options.png_dir.is_none() {
+            return;
+        }
+
+        let frame = self.frame();
+
+        if let Some(png_dir) = &options.png_dir {
+            let path = png_dir.join(format!("frame_{round:04}.png"));
+
+            frames::write_png(&frame, &path).expect("Should be able to write the PNG frame");
+        }
+
+        if options.animate {
+            frames::print_ansi(
+                &frame,
+                &format!("Round {round}: removed {}", self.last_removed.len()),
+            );
+
+            std::thread::sleep(std::time::Duration::from_millis(ANIMATION_FRAME_MILLIS));
+        }
     }
 }
 
@@ -98,22 +603,31 @@ fn main() {
     let file = std::fs::read_to_string(INPUT_PATH)
         .expect("INPUT_PATH should contain the path of the input file");
 
-    let mut grid = Grid::from_file(&file);
+    let options = Options::from_args(std::env::args().skip(1)).unwrap();
 
-    let mut paper_count = grid.count_paper();
+    if let Some(png_dir) = &options.png_dir {
+        std::fs::create_dir_all(png_dir).expect("Should be able to create the PNG directory");
+    }
 
-    let mut total_removed = 0;
+    let mut grid = Grid::from_file(&file, options.rule.clone());
 
-    // This value of 1 is meaningless, it just needs to be primed to != 0 before the loop
-    let mut just_removed = 1;
+    // Frame 0 shows the grid before anything is removed
+    grid.export_frame(&options, 0);
+
+    let mut depth_map = DepthMap::new(&grid);
+
+    let mut total_removed = 0;
 
     // Repeatedly remove the accessible paper until no more are accesssible.
-    while just_removed != 0 {
-        grid.remove_acccessible();
+    for round in 1.. {
+        let just_removed = grid.remove_acccessible();
 
-        let new_paper_count = grid.count_paper();
+        depth_map.record_round(&grid, round);
 
-        just_removed = paper_count - new_paper_count;
+        // The final round removes nothing, so there is no new frame to show
+        if just_removed != 0 {
+            grid.export_frame(&options, round);
+        }
 
         // Print how many were removed at each step
         println!("Removed {} accesssible paper rolls.", just_removed);
@@ -121,8 +635,186 @@ fn main() {
         // Count the total number removed
         total_removed += just_removed;
 
-        paper_count = new_paper_count;
+        if just_removed == 0 {
+            break;
+        }
     }
 
     println!("Removed {} paper rolls in total.", total_removed);
+
+    if options.depth_map {
+        println!();
+        print!("{depth_map}");
+    }
+
+    if options.depth_histogram {
+        println!();
+        depth_map.print_histogram();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift generator, so the random cases are the same on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    /// The number of rolls removed in each round, ending with the round which removes none
+    fn worklist_rounds(file: &str, rule: &Rule) -> Vec<usize> {
+        let mut grid = Grid::from_file(file, rule.clone());
+        let mut rounds = vec![];
+
+        loop {
+            rounds.push(grid.remove_acccessible());
+
+            if rounds.last() == Some(&0) {
+                return rounds;
+            }
+        }
+    }
+
+    /// The same as worklist_rounds, but recounting every neighbour of every position each round
+    fn rescan_rounds(file: &str, rule: &Rule) -> Vec<usize> {
+        let mut paper: Vec<Vec<bool>> = file
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|character| character == PAPER_CHAR)
+                    .collect()
+            })
+            .collect();
+
+        let height = paper.len() as isize;
+        let width = paper.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut paper {
+            row.resize(width, false);
+        }
+        let width = width as isize;
+
+        let offsets = rule.neighbourhood.offsets();
+        let mut rounds = vec![];
+
+        loop {
+            let mut removed = vec![];
+
+            for row in 0..height {
+                for col in 0..width {
+                    if !paper[row as usize][col as usize] {
+                        continue;
+                    }
+
+                    let paper_neighbours = offsets
+                        .iter()
+                        .filter(|&&(row_offset, col_offset)| {
+                            let (mut target_row, mut target_col) =
+                                (row + row_offset, col + col_offset);
+
+                            if matches!(rule.edge_mode, EdgeMode::Torus) {
+                                target_row = target_row.rem_euclid(height);
+                                target_col = target_col.rem_euclid(width);
+                            }
+
+                            if (0..height).contains(&target_row) && (0..width).contains(&target_col)
+                            {
+                                paper[target_row as usize][target_col as usize]
+                            } else {
+                                matches!(rule.edge_mode, EdgeMode::Paper)
+                            }
+                        })
+                        .count();
+
+                    if rule.allows(paper_neighbours) {
+                        removed.push((row as usize, col as usize));
+                    }
+                }
+            }
+
+            for &(row, col) in &removed {
+                paper[row][col] = false;
+            }
+
+            rounds.push(removed.len());
+
+            if removed.is_empty() {
+                return rounds;
+            }
+        }
+    }
+
+    /// The short lines are padded with empty positions, so wrapping around lands on those
+    /// rather than on the start of the short line
+    #[test]
+    fn ragged_torus_matches_rescan() {
+        let file = "..@@\n.@..@@@\n@@.\n..@.@@.\n";
+        let rule = Rule {
+            edge_mode: EdgeMode::Torus,
+            ..Rule::puzzle()
+        };
+
+        assert_eq!(worklist_rounds(file, &rule), [10, 1, 0]);
+        assert_eq!(rescan_rounds(file, &rule), [10, 1, 0]);
+    }
+
+    #[test]
+    fn random_grids_match_rescan() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..3000 {
+            // Ragged lines, sometimes empty, mostly filled with paper
+            let file: String = (0..1 + rng.below(7))
+                .map(|_| {
+                    let line: String = (0..rng.below(9))
+                        .map(|_| if rng.below(3) == 0 { '.' } else { '@' })
+                        .collect();
+                    line + "\n"
+                })
+                .collect();
+
+            let neighbourhood = match rng.below(4) {
+                0 => Neighbourhood::VonNeumann,
+                1 => Neighbourhood::Moore,
+                2 => Neighbourhood::Radius(2),
+                _ => Neighbourhood::Custom(
+                    (0..1 + rng.below(5))
+                        .map(|_| (rng.below(9) as isize - 4, rng.below(9) as isize - 4))
+                        .collect(),
+                ),
+            };
+            let comparison = [
+                Comparison::LessThan,
+                Comparison::LessOrEqual,
+                Comparison::Equal,
+                Comparison::GreaterOrEqual,
+                Comparison::GreaterThan,
+            ][rng.below(5)];
+            let edge_mode = [EdgeMode::Wall, EdgeMode::Torus, EdgeMode::Paper][rng.below(3)];
+
+            let rule = Rule {
+                neighbourhood,
+                comparison,
+                threshold: rng.below(7),
+                edge_mode,
+            };
+
+            assert_eq!(
+                worklist_rounds(&file, &rule),
+                rescan_rounds(&file, &rule),
+                "Grid:\n{file}"
+            );
+        }
+    }
 }
@@ -1,137 +1,212 @@
-use std::ops::RangeInclusive;
+use std::{
+    fmt::{Debug, Display},
+    io::BufRead,
+    ops::RangeInclusive,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use common::{Integer, RangeSet};
 
 const EXAMPLE: bool = false;
 const INPUT_PATH: &str = if EXAMPLE { "example" } else { "input" };
 
+/// Split a range of the form "start-end" into its start and end
+///
+/// Either number may be negative, so the dash separating them is the first one
+/// which is not the sign of the start, e.g. "-5--2" splits into "-5" and "-2".
+fn split_range(range_str: &str) -> Option<(&str, &str)> {
+    let (separator_index, _) = range_str
+        .char_indices()
+        .skip(1)
+        .find(|&(_, character)| character == '-')?;
+
+    Some((
+        &range_str[..separator_index],
+        &range_str[separator_index + 1..],
+    ))
+}
+
 /// Create a Vec of RangeInclusive structs from the range section of the file
-fn parse_ranges(range_section: &str) -> Vec<RangeInclusive<u64>> {
+fn parse_ranges<T: FromStr<Err: Debug>>(range_section: &str) -> Vec<RangeInclusive<T>> {
     // Each range is on a separate line
     range_section
-        .trim()
         .trim()
         .split("\n")
         .map(|range_str| {
             // The start and end of the ranges are separated by a dash
-            let (start_str, end_str) = range_str
-                .trim()
-                .split_once("-")
+            let (start_str, end_str) = split_range(range_str.trim())
                 .expect("Each range should contain a - between the start and end");
 
             RangeInclusive::new(
-                start_str.parse().expect("start should be parseable as u64"),
-                end_str.parse().expect("end should be parseable as u64"),
+                start_str
+                    .parse()
+                    .expect("start should be parseable as the key type"),
+                end_str
+                    .parse()
+                    .expect("end should be parseable as the key type"),
             )
         })
         .collect()
 }
 
 /// Create a Vec of ingredient IDs from the ID section of the file
-fn parse_ingredients(ingredient_section: &str) -> Vec<u64> {
+fn parse_ingredients<T: FromStr<Err: Debug>>(ingredient_section: &str) -> Vec<T> {
     // Each ingredient is on a separate line
     ingredient_section
         .trim()
         .split("\n")
         .map(|id_str| {
             id_str
+                .trim()
                 .parse()
-                .expect("Each ingredient ID should be parseable as a u64")
+                .expect("Each ingredient ID should be parseable as the key type")
         })
         .collect()
 }
 
-/// Combine the two ranges given into one if they overlap
-///
-/// If the two ranges do not overlap, None is returned
-fn range_union(
-    range1: &RangeInclusive<u64>,
-    range2: &RangeInclusive<u64>,
-) -> Option<RangeInclusive<u64>> {
-    // Sort the ranges by their starts
-    let (first_range, second_range) = if range1.start() < range2.start() {
-        (range1, range2)
-    } else {
-        (range2, range1)
-    };
+/// The options which can be given on the command line
+struct Options {
+    // The integer type used for the IDs
+    key_type: String,
+    // A file to load the combined ranges from, instead of the range section of the input
+    load_path: Option<PathBuf>,
+    // A file to save the combined ranges to
+    save_path: Option<PathBuf>,
+    // Whether to answer queries from stdin instead of solving the puzzle
+    query: bool,
+}
 
-    if first_range.contains(second_range.end()) {
-        // If the first range contains the second range entirely, clone the first range.
-        Some(first_range.clone())
-    } else if second_range.contains(first_range.end())
-        || first_range.end() + 1 == *second_range.start()
-    {
-        // If the ranges overlap partially or touch at their ends,
-        // make a new range from the start of the first to the end of the second.
-        Some(*first_range.start()..=*second_range.end())
-    } else {
-        None
+impl Options {
+    /// Build the options from command line arguments
+    ///
+    /// --key-type <type> chooses the integer type used for the IDs, defaulting to u64.
+    /// --load <path> and --save <path> read and write the combined ranges as a binary file.
+    /// --query checks each ID given on stdin, one per line, instead of solving the puzzle.
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, &'static str> {
+        let mut options = Options {
+            key_type: String::from("u64"),
+            load_path: None,
+            save_path: None,
+            query: false,
+        };
+
+        while let Some(flag) = args.next() {
+            if flag == "--query" {
+                options.query = true;
+                continue;
+            }
+
+            let value = args
+                .next()
+                .ok_or("Each argument should be followed by a value!")?;
+
+            match flag.as_str() {
+                "--key-type" => options.key_type = value,
+                "--load" => options.load_path = Some(PathBuf::from(value)),
+                "--save" => options.save_path = Some(PathBuf::from(value)),
+                _ => return Err("Unrecognised argument!"),
+            }
+        }
+
+        Ok(options)
     }
 }
 
-/// Combine all overlapping or touching ranges given, and return the resulting ranges.
-/// The returned ranges will be all be separated by 1 or more.
-fn simplify_ranges(ranges: &[RangeInclusive<u64>]) -> Vec<RangeInclusive<u64>> {
-    if ranges.is_empty() {
-        return vec![];
-    }
+/// Solve both parts of the puzzle, given the combined ranges and the ingredient section of the file
+fn solve<T: Integer + FromStr<Err: Debug>>(ranges: &RangeSet<T>, ingredient_section: &str) {
+    let ingredients = parse_ingredients(ingredient_section);
+
+    // Find the combined range covering each ingredient, if any, in a single sweep over them all
+    let covering_ranges = ranges.ranges_containing_all(&ingredients);
+
+    let fresh_ingredients = covering_ranges
+        .iter()
+        .filter(|covering_range| covering_range.is_some())
+        .count();
+
+    println!("There are {fresh_ingredients} fresh ingredients on-hand.");
+
+    // Since the ranges no longer overlap, the number of IDs in the set counts each one only once
+    let possible_fresh_ingredients = ranges.len();
+
+    println!("There are {possible_fresh_ingredients} possible fresh ingredients.");
+}
+
+/// Check each ID given on stdin, one per line, printing whether it is fresh and the range covering it
+fn answer_queries<T: Integer + Display + FromStr>(ranges: &RangeSet<T>) {
+    for line in std::io::stdin().lock().lines() {
+        let line = line.expect("Should be able to read from stdin");
+        let id_str = line.trim();
 
-    // Sort the ranges by their endings, such that the ranges which end last come last
-    // We sort in this way because we will later be popping from the right of the Vec.
-    // If we sorted by start instead of end, some overlapping ranges would not get combined later
-    let mut ranges_sorted = Vec::from(ranges);
-    ranges_sorted.sort_by_key(|range| *range.end());
-
-    let mut ranges_simplified = vec![];
-
-    // We will pull out one range at a time to work on
-    let mut working_range = ranges_sorted
-        .pop()
-        .expect("ranges_sorted should not be empty");
-
-    // Keep pulling out ranges until the sorted Vec is empty
-    while let Some(next_range) = ranges_sorted.pop() {
-        if let Some(overlap) = range_union(&working_range, &next_range) {
-            // If the next range overlaps with the one we are working on,
-            // replace the working range with its union with the next range
-            working_range = overlap;
-        } else {
-            // If they do not overlap, we are done with the working range,
-            // so we can move on to working on the next range.
-            // We can be certain that there are no further overlaps because
-            // of the sorting order used earlier.
-            ranges_simplified.push(working_range);
-            working_range = next_range;
+        if id_str.is_empty() {
+            continue;
         }
-    }
 
-    ranges_simplified.push(working_range);
+        // A bad query should not end the session, so it is reported rather than panicking
+        let Ok(id) = id_str.parse::<T>() else {
+            println!("{id_str}: invalid ID");
+            continue;
+        };
 
-    ranges_simplified
+        match ranges.range_containing(id) {
+            Some(range) => println!("{id}: fresh ({}-{})", range.start(), range.end()),
+            None => println!("{id}: spoiled"),
+        }
+    }
 }
 
-fn main() {
-    let file = std::fs::read_to_string(INPUT_PATH)
-        .expect("INPUT_PATH should contain the path of the input file");
+/// Run the program with the IDs read as the integer type T
+fn run<T: Integer + Display + FromStr<Err: Debug>>(options: &Options) {
+    // The input file is not needed when the ranges are loaded and only queries are answered
+    let file = if options.load_path.is_none() || !options.query {
+        std::fs::read_to_string(INPUT_PATH)
+            .expect("INPUT_PATH should contain the path of the input file")
+    } else {
+        String::new()
+    };
 
-    let (range_section, ingredient_section) = file
-        .split_once("\n\n")
-        .expect("There should be a blank line separating the sections");
+    let (range_section, ingredient_section) = file.split_once("\n\n").unwrap_or((&file, ""));
 
-    let ranges = parse_ranges(range_section);
-    let ingredients = parse_ingredients(ingredient_section);
+    // Combining the ranges into a RangeSet eliminates any overlaps between them
+    let ranges: RangeSet<T> = match &options.load_path {
+        Some(load_path) => {
+            let bytes = std::fs::read(load_path).expect("Should be able to read the ranges file");
 
-    let fresh_ingredients = ingredients
-        .iter()
-        .filter(|ingredient| ranges.iter().any(|range| range.contains(ingredient)))
-        .count();
+            RangeSet::from_bytes(&bytes).unwrap()
+        }
+        None => parse_ranges(range_section).into_iter().collect(),
+    };
 
-    println!("There are {fresh_ingredients} fresh ingredients on-hand.");
+    if let Some(save_path) = &options.save_path {
+        std::fs::write(save_path, ranges.to_bytes())
+            .expect("Should be able to write the ranges file");
+    }
 
-    // Add up the lengths of all the ranges after combining them together to eliminate overlaps
-    let possible_fresh_ingredients = simplify_ranges(&ranges)
-        .iter()
-        // RangeInclusive doesn't have a len() method, so we must calculate it manually for each range
-        .map(|range| range.end() - range.start() + 1)
-        .sum::<u64>();
+    if options.query {
+        answer_queries(&ranges);
+    } else {
+        assert!(
+            !ingredient_section.is_empty(),
+            "There should be a blank line separating the sections"
+        );
 
-    println!("There are {possible_fresh_ingredients} possible fresh ingredients.");
+        solve(&ranges, ingredient_section);
+    }
+}
+
+fn main() {
+    let options = Options::from_args(std::env::args().skip(1)).unwrap();
+
+    match options.key_type.as_str() {
+        "u8" => run::<u8>(&options),
+        "u16" => run::<u16>(&options),
+        "u32" => run::<u32>(&options),
+        "u64" => run::<u64>(&options),
+        "i8" => run::<i8>(&options),
+        "i16" => run::<i16>(&options),
+        "i32" => run::<i32>(&options),
+        "i64" => run::<i64>(&options),
+        _ => panic!("The key type should be one of u8, u16, u32, u64, i8, i16, i32 or i64"),
+    }
 }
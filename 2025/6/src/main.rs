@@ -1,15 +1,272 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Range};
+
+use common::BigUint;
 
 const EXAMPLE: bool = false;
 const INPUT_PATH: &str = if EXAMPLE { "example" } else { "input" };
 const OPERAND_FORMAT_WIDTH: usize = 4;
 const ANSWER_FORMAT_WIDTH: usize = 15;
+const TAB_WIDTH: usize = 8;
 
 /// The valid operators in Cephalopod Math
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 enum Operator {
     Multiply,
     Add,
+    Subtract,
+    Divide,
+    Remainder,
+    Power,
+    Min,
+    Max,
+}
+
+impl Operator {
+    /// The identity element of the operator, if it has one for every Number type
+    ///
+    /// Only addition, multiplication and max have one. Min would need the largest possible value,
+    /// which arbitrary-precision numbers do not have.
+    fn identity(&self) -> Option<u64> {
+        match self {
+            Operator::Multiply => Some(1),
+            Operator::Add | Operator::Max => Some(0),
+            Operator::Subtract
+            | Operator::Divide
+            | Operator::Remainder
+            | Operator::Power
+            | Operator::Min => None,
+        }
+    }
+
+    /// How tightly the operator binds in an infix expression, where higher binds tighter
+    ///
+    /// ^ binds tightest, then * / %, then + -, with min and max loosest of all.
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Power => 3,
+            Operator::Multiply | Operator::Divide | Operator::Remainder => 2,
+            Operator::Add | Operator::Subtract => 1,
+            Operator::Min | Operator::Max => 0,
+        }
+    }
+
+    /// Returns true if a chain of this operator groups from the right in an infix expression,
+    /// i.e. a ^ b ^ c is a ^ (b ^ c). Every other operator groups from the left.
+    fn is_right_associative(&self) -> bool {
+        matches!(self, Operator::Power)
+    }
+}
+
+/// The ways evaluating a problem can fail
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EvaluationError {
+    /// The result was too large for the number type
+    Overflow,
+    /// A power would have more than MAX_POWER_BITS bits, so would take too long to compute
+    TooLarge,
+    /// A subtraction would have gone below 0
+    Underflow,
+    DivisionByZero,
+    /// There were no operands, and the operator has no identity element
+    NoOperands,
+    /// An infix expression did not have exactly one fewer operators than operands
+    MismatchedOperators,
+}
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluationError::Overflow => "overflow",
+            EvaluationError::TooLarge => "result too large to compute",
+            EvaluationError::Underflow => "underflow",
+            EvaluationError::DivisionByZero => "division by zero",
+            EvaluationError::NoOperands => "no operands",
+            EvaluationError::MismatchedOperators => "mismatched operators and operands",
+        }
+        .fmt(f)
+    }
+}
+
+// The most bits an exact power may have, since a large enough exponent would never finish
+const MAX_POWER_BITS: usize = 1 << 18;
+
+/// The number types which problems can be evaluated with
+trait Number: Clone + Display + From<u64> {
+    /// Apply the operator to the two operands, in the order lhs (operator) rhs
+    fn apply(operator: Operator, lhs: Self, rhs: Self) -> Result<Self, EvaluationError>;
+}
+
+impl Number for u64 {
+    fn apply(operator: Operator, lhs: u64, rhs: u64) -> Result<u64, EvaluationError> {
+        match operator {
+            Operator::Multiply => lhs.checked_mul(rhs).ok_or(EvaluationError::Overflow),
+            Operator::Add => lhs.checked_add(rhs).ok_or(EvaluationError::Overflow),
+            Operator::Subtract => lhs.checked_sub(rhs).ok_or(EvaluationError::Underflow),
+            Operator::Divide => lhs.checked_div(rhs).ok_or(EvaluationError::DivisionByZero),
+            Operator::Remainder => lhs.checked_rem(rhs).ok_or(EvaluationError::DivisionByZero),
+            Operator::Power => match u32::try_from(rhs) {
+                Ok(exponent) => lhs.checked_pow(exponent).ok_or(EvaluationError::Overflow),
+                // Only 0 and 1 can be raised to such a large power without overflowing
+                Err(_) if lhs <= 1 => Ok(lhs),
+                Err(_) => Err(EvaluationError::Overflow),
+            },
+            Operator::Min => Ok(lhs.min(rhs)),
+            Operator::Max => Ok(lhs.max(rhs)),
+        }
+    }
+}
+
+impl Number for BigUint {
+    fn apply(operator: Operator, lhs: BigUint, rhs: BigUint) -> Result<BigUint, EvaluationError> {
+        match operator {
+            Operator::Multiply => Ok(&lhs * &rhs),
+            Operator::Add => Ok(&lhs + &rhs),
+            Operator::Subtract => lhs.checked_sub(&rhs).ok_or(EvaluationError::Underflow),
+            Operator::Divide => lhs
+                .div_rem(&rhs)
+                .map(|(quotient, _)| quotient)
+                .ok_or(EvaluationError::DivisionByZero),
+            Operator::Remainder => lhs
+                .div_rem(&rhs)
+                .map(|(_, remainder)| remainder)
+                .ok_or(EvaluationError::DivisionByZero),
+            // 0 and 1 stay the same when raised to any positive power
+            Operator::Power if lhs <= BigUint::from(1u64) && !rhs.is_zero() => Ok(lhs),
+            Operator::Power => match rhs.to_u32() {
+                // The power has at most bits * exponent bits, so check that before computing it
+                Some(exponent)
+                    if (lhs.bits() as u64).saturating_mul(exponent as u64)
+                        <= MAX_POWER_BITS as u64 =>
+                {
+                    Ok(lhs.pow(exponent))
+                }
+                _ => Err(EvaluationError::TooLarge),
+            },
+            Operator::Min => Ok(lhs.min(rhs)),
+            Operator::Max => Ok(lhs.max(rhs)),
+        }
+    }
+}
+
+/// Why a worksheet could not be parsed, and where
+///
+/// Problems are counted from 0, in the order they appear from left to right.
+/// Rows and columns are counted from 0 as indices into the file,
+/// but are displayed counting from 1 as an editor would.
+#[derive(Debug)]
+enum ParseError {
+    /// The worksheet has no lines
+    Empty,
+    /// The last line contains digits, so there is no line of operators
+    MissingOperatorRow,
+    /// An operator of the problem, at the given column and row, could not be parsed
+    BadOperator {
+        problem: usize,
+        column: usize,
+        row: usize,
+    },
+    /// The problem has a number of operators which is neither 1 nor one fewer than its operands
+    WrongOperatorCount {
+        problem: usize,
+        column: usize,
+        operators: usize,
+        operands: usize,
+    },
+    /// An operand of the problem could not be parsed, from the given column and row.
+    /// In the columnar reading, each operand is read down a whole column, so there is no row.
+    BadOperand {
+        problem: usize,
+        column: usize,
+        row: Option<usize>,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "The worksheet is empty"),
+            ParseError::MissingOperatorRow => {
+                write!(
+                    f,
+                    "The last line of the worksheet should contain the operators"
+                )
+            }
+            ParseError::BadOperator {
+                problem,
+                column,
+                row,
+            } => write!(
+                f,
+                "Could not parse an operator of problem {problem} at line {}, column {}",
+                row + 1,
+                column + 1
+            ),
+            ParseError::WrongOperatorCount {
+                problem,
+                column,
+                operators,
+                operands,
+            } => write!(
+                f,
+                "Problem {problem} at column {} has {operators} operators for {operands} operands",
+                column + 1
+            ),
+            ParseError::BadOperand {
+                problem,
+                column,
+                row: Some(row),
+            } => write!(
+                f,
+                "Could not parse an operand of problem {problem} at line {}, column {}",
+                row + 1,
+                column + 1
+            ),
+            ParseError::BadOperand {
+                problem,
+                column,
+                row: None,
+            } => write!(
+                f,
+                "Could not parse an operand of problem {problem} in column {}",
+                column + 1
+            ),
+        }
+    }
+}
+
+/// Why the answers to a worksheet could not be totalled
+enum TotalError {
+    /// The problem at the given index could not be evaluated
+    Problem(usize, EvaluationError),
+    /// The grand total overflowed when adding the answer to the problem at the given index
+    GrandTotalOverflow(usize),
+}
+
+impl TotalError {
+    /// Returns true if the error is an overflow, which computing the answers exactly would avoid
+    fn is_overflow(&self) -> bool {
+        matches!(
+            self,
+            TotalError::Problem(_, EvaluationError::Overflow) | TotalError::GrandTotalOverflow(_)
+        )
+    }
+}
+
+impl Display for TotalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TotalError::Problem(index, error) => {
+                write!(
+                    f,
+                    "The problem at index {index} could not be evaluated: {error}"
+                )
+            }
+            TotalError::GrandTotalOverflow(index) => write!(
+                f,
+                "The grand total overflowed when adding the problem at index {index}"
+            ),
+        }
+    }
 }
 
 impl TryFrom<&str> for Operator {
@@ -19,58 +276,196 @@ impl TryFrom<&str> for Operator {
         match value {
             "*" => Ok(Operator::Multiply),
             "+" => Ok(Operator::Add),
-            _ => Err("Only * and + are supported operators!"),
+            "-" => Ok(Operator::Subtract),
+            "/" => Ok(Operator::Divide),
+            "%" => Ok(Operator::Remainder),
+            "^" => Ok(Operator::Power),
+            "min" => Ok(Operator::Min),
+            "max" => Ok(Operator::Max),
+            _ => Err("Only *, +, -, /, %, ^, min and max are supported operators!"),
         }
     }
 }
 
-/// A Cephalopod Math problem is a set of integer operands linked by a single commutative operator
+// Display an Operator using the same string it is parsed from
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operator::Multiply => "*",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Divide => "/",
+            Operator::Remainder => "%",
+            Operator::Power => "^",
+            Operator::Min => "min",
+            Operator::Max => "max",
+        }
+        .fmt(f)
+    }
+}
+
+/// The operators linking the operands of a problem
+#[derive(Debug, PartialEq)]
+enum Operators {
+    /// The same operator between every pair of operands, as in the original worksheet format
+    Single(Operator),
+    /// An operator between each consecutive pair of operands, forming an infix expression
+    /// which is evaluated respecting precedence. There are always at least 2 operators.
+    Infix(Vec<Operator>),
+}
+
+/// A Cephalopod Math problem is a list of integer operands linked by operators
+///
+/// The operands are kept in the order they are read from the worksheet, which is also the order
+/// they are evaluated in: top-to-bottom in the naive reading, and right-to-left in the columnar one.
+#[derive(Debug, PartialEq)]
 struct Problem {
     operands: Vec<u64>,
-    operator: Operator,
+    operators: Operators,
 }
 
 impl Problem {
-    /// Create a new problem with the given operator, but an empty list of operandd
-    fn new(operator: Operator) -> Problem {
-        Problem {
-            operands: vec![],
-            operator,
+    /// Iterate over the operators in between the operands, in order
+    fn operators_between(&self) -> Box<dyn Iterator<Item = Operator> + '_> {
+        match &self.operators {
+            Operators::Single(operator) => Box::new(std::iter::repeat(*operator)),
+            Operators::Infix(operators) => Box::new(operators.iter().copied()),
         }
     }
 
-    /// Add the given operand to the problem
-    fn push_operand(&mut self, operand: u64) {
-        self.operands.push(operand);
+    /// Evaluate the problem, as either a chain of a single operator or an infix expression
+    fn evaluate<N: Number>(&self) -> Result<N, EvaluationError> {
+        match &self.operators {
+            Operators::Single(operator) => self.evaluate_single(*operator),
+            Operators::Infix(operators) => self.evaluate_infix(operators),
+        }
     }
 
-    /// Evaluate the expression by repeatedly applying the operator to the operands
+    /// Evaluate the expression by repeatedly applying the operator to the operands, from the first
+    ///
+    /// E.g. the operands [a, b, c] with the operator - evaluate to (a - b) - c.
     ///
     /// If the expression has no operands, the identity element of the operator is returned.
-    /// I.e. 0 for addition, 1 for multiplication
-    fn evaluate(&self) -> u64 {
-        // Get the u64 method and identity element corresponding to the operator
-        let (operation, mut accumulator): (fn(u64, u64) -> u64, u64) = match self.operator {
-            Operator::Multiply => (u64::strict_mul, 1),
-            Operator::Add => (u64::strict_add, 0),
+    /// I.e. 0 for addition, 1 for multiplication.
+    /// An Err is returned if there are no operands and the operator has no identity element,
+    /// or if any step of the evaluation fails, e.g. by overflowing.
+    fn evaluate_single<N: Number>(&self, operator: Operator) -> Result<N, EvaluationError> {
+        let mut operands = self.operands.iter();
+
+        // Start from the first operand, which is the same as starting from the identity element
+        // for operators which have one
+        let mut accumulator = N::from(
+            operands
+                .next()
+                .copied()
+                .or(operator.identity())
+                .ok_or(EvaluationError::NoOperands)?,
+        );
+
+        // Repeatedly apply the operator on the remaining operands
+        for &operand in operands {
+            accumulator = N::apply(operator, accumulator, N::from(operand))?;
+        }
+
+        Ok(accumulator)
+    }
+
+    /// Evaluate the infix expression operand operator operand ... operator operand,
+    /// applying higher precedence operators first, e.g. a + b * c is a + (b * c).
+    ///
+    /// This is the shunting-yard algorithm, except that each operator is applied as soon as it
+    /// would be output, rather than building up the expression in postfix form.
+    fn evaluate_infix<N: Number>(&self, operators: &[Operator]) -> Result<N, EvaluationError> {
+        if operators.len() + 1 != self.operands.len() {
+            return Err(EvaluationError::MismatchedOperators);
+        }
+
+        // The values not yet used up, and the operators waiting to be applied to them
+        let mut values = vec![N::from(self.operands[0])];
+        let mut pending_operators: Vec<Operator> = vec![];
+
+        // Replace the last two values with the result of applying the operator to them
+        let apply_last = |values: &mut Vec<N>, operator: Operator| {
+            let rhs = values
+                .pop()
+                .expect("Each operator should have a right operand");
+            let lhs = values
+                .pop()
+                .expect("Each operator should have a left operand");
+
+            values.push(N::apply(operator, lhs, rhs)?);
+
+            Ok(())
         };
 
-        // Repeatedly apply the operator on the operands
-        for operand in &self.operands {
-            accumulator = operation(accumulator, *operand);
+        for (&operator, &operand) in operators.iter().zip(&self.operands[1..]) {
+            // Any pending operator which binds at least as tightly must be applied first,
+            // unless the operators are the same right-associative one
+            while let Some(&pending) = pending_operators.last()
+                && (pending.precedence() > operator.precedence()
+                    || pending.precedence() == operator.precedence()
+                        && !operator.is_right_associative())
+            {
+                pending_operators.pop();
+                apply_last(&mut values, pending)?;
+            }
+
+            pending_operators.push(operator);
+            values.push(N::from(operand));
+        }
+
+        // Whatever is left can be applied from the tightest binding, i.e. the most recent
+        while let Some(pending) = pending_operators.pop() {
+            apply_last(&mut values, pending)?;
         }
 
-        accumulator
+        Ok(values.pop().expect("A single value should remain"))
     }
 
-    fn parse_from_file(file: &str, naive: bool) -> Vec<Problem> {
-        // This vector will form the return value
-        let mut problems = vec![];
+    /// Convert the file to a 2D vector of characters, where each row is a line of the file
+    ///
+    /// Tabs are expanded to the next multiple of TAB_WIDTH columns, blank lines at the end are dropped,
+    /// and every row is padded with spaces to the width of the longest, since editors often trim
+    /// trailing spaces.
+    fn file_to_grid(file: &str) -> Vec<Vec<char>> {
+        let mut file_grid: Vec<Vec<char>> = file
+            .lines()
+            .map(|line| {
+                let mut row = vec![];
 
-        // Convert the file to a 2D vector of characters, where each row is a line of the file
-        let file_grid: Vec<Vec<char>> = file.lines().map(|line| line.chars().collect()).collect();
-        let grid_height = file_grid.len();
-        let grid_width = file_grid[0].len();
+                for character in line.chars() {
+                    if character == '\t' {
+                        row.resize((row.len() / TAB_WIDTH + 1) * TAB_WIDTH, ' ');
+                    } else {
+                        row.push(character);
+                    }
+                }
+
+                row
+            })
+            .collect();
+
+        while file_grid
+            .last()
+            .is_some_and(|row| row.iter().all(|character| character.is_whitespace()))
+        {
+            file_grid.pop();
+        }
+
+        let grid_width = file_grid.iter().map(Vec::len).max().unwrap_or(0);
+
+        for row in &mut file_grid {
+            row.resize(grid_width, ' ');
+        }
+
+        file_grid
+    }
+
+    /// Find the lower (inclusive) and upper (exclusive) column bounds of each problem in the grid
+    ///
+    /// Problems are separated by columns containing only spaces.
+    fn problem_bounds(file_grid: &[Vec<char>]) -> Vec<Range<usize>> {
+        let grid_width = file_grid.first().map_or(0, Vec::len);
 
         // Get the index of each column containing only spaces
         let mut empty_cols: Vec<usize> = (0..grid_width)
@@ -80,57 +475,324 @@ impl Problem {
         // The final problem is not proceeded by a column of spaces, but we can pretend
         empty_cols.push(grid_width);
 
-        // The start_col and end_col will become the lower (inclusive) and
-        // upper (exclusive) index bounds of the each problem in the file
+        let mut bounds = vec![];
+
         let mut start_col = 0;
         for end_col in empty_cols {
-            // The last line of the file contains the operators
-            let operator_string: String = file_grid[grid_height - 1][start_col..end_col]
-                .iter()
-                .collect();
+            // Several empty columns in a row do not separate any problems
+            if start_col != end_col {
+                bounds.push(start_col..end_col);
+            }
+
+            // The next problem starts just after the empty column
+            start_col = end_col + 1;
+        }
 
-            // Trim the operator string, convert it to an Operator enum,
-            // and create a new empty problem with that operator.
-            let mut problem = Problem::new(Operator::try_from(operator_string.trim()).unwrap());
+        bounds
+    }
+
+    /// Split a slice of a line into its whitespace-separated tokens, each with its starting index
+    fn tokenise(slice: &[char]) -> Vec<(usize, String)> {
+        let mut tokens: Vec<(usize, String)> = vec![];
+        let mut in_token = false;
+
+        for (index, &character) in slice.iter().enumerate() {
+            if character.is_whitespace() {
+                in_token = false;
+            } else if in_token {
+                tokens
+                    .last_mut()
+                    .expect("A token should have been started")
+                    .1
+                    .push(character);
+            } else {
+                tokens.push((index, String::from(character)));
+                in_token = true;
+            }
+        }
+
+        tokens
+    }
+
+    /// Parse the worksheet into problems, reading the operands naively or in columns
+    ///
+    /// The lines at the end of the worksheet without any digits hold the operators.
+    /// Each problem has either a single operator, applied between all of its operands,
+    /// or one operator for each gap between its operands, forming an infix expression.
+    /// Operators are read in the same direction as the operands: top-to-bottom,
+    /// and left-to-right in the naive reading or right-to-left in the columnar one.
+    fn parse_from_file(file: &str, naive: bool) -> Result<Vec<Problem>, ParseError> {
+        // This vector will form the return value
+        let mut problems = vec![];
+
+        let file_grid = Problem::file_to_grid(file);
+        let grid_height = file_grid.len();
+
+        if file_grid.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        // The operands end at the last line with any digits, and the operators take up the rest
+        let operand_rows = file_grid
+            .iter()
+            .rposition(|row| row.iter().any(char::is_ascii_digit))
+            .map_or(0, |last_operand_row| last_operand_row + 1);
+
+        if operand_rows == grid_height {
+            return Err(ParseError::MissingOperatorRow);
+        }
+
+        for (index, columns) in Problem::problem_bounds(&file_grid).into_iter().enumerate() {
+            let (start_col, end_col) = (columns.start, columns.end);
+            let mut operands = vec![];
 
             if naive {
                 // In the naive approach, we read the operands from left-to-right between the column bounds
-                // Note that we are skipping the last row, as it has the operator, not the operand
-                for file_row in file_grid.iter().take(grid_height - 1) {
+                // Note that we are skipping the last rows, as they have the operators, not the operands
+                for (row, file_row) in file_grid.iter().take(operand_rows).enumerate() {
                     let operand_string: String = file_row[start_col..end_col].iter().collect();
 
-                    problem.push_operand(operand_string.trim().parse().unwrap());
+                    let operand =
+                        operand_string
+                            .trim()
+                            .parse()
+                            .map_err(|_| ParseError::BadOperand {
+                                problem: index,
+                                column: start_col,
+                                row: Some(row),
+                            })?;
+
+                    operands.push(operand);
                 }
             } else {
-                // In the correct approach, we read the operands from top-to-bottom between the column bounds
-                for col in start_col..end_col {
+                // In the correct approach, we read the operands from top-to-bottom between the column bounds,
+                // starting from the rightmost column and moving left
+                for col in (start_col..end_col).rev() {
                     let mut operand_string = String::new();
 
-                    for file_row in file_grid.iter().take(grid_height - 1) {
+                    for file_row in file_grid.iter().take(operand_rows) {
                         operand_string.push(file_row[col]);
                     }
 
-                    problem.push_operand(operand_string.trim().parse().unwrap());
+                    // The operand is read down a whole column, so no single row is to blame
+                    let operand =
+                        operand_string
+                            .trim()
+                            .parse()
+                            .map_err(|_| ParseError::BadOperand {
+                                problem: index,
+                                column: col,
+                                row: None,
+                            })?;
+
+                    operands.push(operand);
                 }
             }
 
-            problems.push(problem);
+            let mut operators = vec![];
 
-            // The next problem starts just after the empty column
-            start_col = end_col + 1;
+            for (row, file_row) in file_grid.iter().enumerate().skip(operand_rows) {
+                let mut tokens = Problem::tokenise(&file_row[start_col..end_col]);
+
+                if !naive {
+                    tokens.reverse();
+                }
+
+                for (offset, token) in tokens {
+                    // Convert each token to an Operator enum
+                    let operator = Operator::try_from(token.as_str()).map_err(|_| {
+                        ParseError::BadOperator {
+                            problem: index,
+                            column: start_col + offset,
+                            row,
+                        }
+                    })?;
+
+                    operators.push(operator);
+                }
+            }
+
+            let operators = match operators.len() {
+                0 => {
+                    return Err(ParseError::BadOperator {
+                        problem: index,
+                        column: start_col,
+                        row: operand_rows,
+                    });
+                }
+                1 => Operators::Single(operators[0]),
+                count if count + 1 == operands.len() => Operators::Infix(operators),
+                count => {
+                    return Err(ParseError::WrongOperatorCount {
+                        problem: index,
+                        column: start_col,
+                        operators: count,
+                        operands: operands.len(),
+                    });
+                }
+            };
+
+            problems.push(Problem {
+                operands,
+                operators,
+            });
         }
 
-        problems
+        Ok(problems)
     }
 }
 
-impl Display for Problem {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let operator_char = match self.operator {
-            Operator::Multiply => '*',
-            Operator::Add => '+',
+impl Problem {
+    /// The number of lines the problem's operands take up in the given layout of the worksheet
+    fn operand_rows(&self, naive: bool) -> usize {
+        if naive {
+            // Each operand has its own line
+            self.operands.len()
+        } else {
+            // Each digit of an operand has its own line
+            self.operands
+                .iter()
+                .map(|operand| operand.to_string().len())
+                .max()
+                .unwrap_or(0)
+        }
+    }
+
+    /// The operators of the problem as they are written in the worksheet, one per line
+    fn operator_strings(&self) -> Vec<String> {
+        match &self.operators {
+            Operators::Single(operator) => vec![operator.to_string()],
+            Operators::Infix(operators) => operators.iter().map(Operator::to_string).collect(),
+        }
+    }
+
+    /// Lay the problem out as its block of lines in the given layout of the worksheet,
+    /// with operand_rows lines of operands followed by operator_rows lines of operators.
+    /// Every line of the block has the same width.
+    ///
+    /// An Err is returned if the problem cannot be laid out such that it would be parsed back the same.
+    fn render_block(
+        &self,
+        naive: bool,
+        operand_rows: usize,
+        operator_rows: usize,
+    ) -> Result<Vec<String>, &'static str> {
+        if self.operands.is_empty() {
+            return Err("A problem needs at least one operand to be rendered!");
+        }
+
+        let operator_strings = self.operator_strings();
+        let operator_width = operator_strings.iter().map(String::len).max().unwrap_or(0);
+        let operand_strings: Vec<String> = self.operands.iter().map(u64::to_string).collect();
+
+        let mut block = vec![];
+
+        let width = if naive {
+            // Each operand is right-aligned on its own line, so the block is as wide as
+            // the widest operand, or the widest operator if that is wider
+            let width = operand_strings
+                .iter()
+                .map(String::len)
+                .chain([operator_width])
+                .max()
+                .expect("There should be at least the operators' width");
+
+            for operand_string in &operand_strings {
+                block.push(format!("{operand_string:>width$}"));
+            }
+
+            width
+        } else {
+            // Each operand is a column of digits read top-to-bottom, with the first operand in
+            // the rightmost column, so the block is exactly as wide as the number of operands.
+            // A wider operator would leave columns without digits, which cannot be parsed.
+            let width = operand_strings.len();
+
+            if operator_width > width {
+                return Err("The operator is wider than the operands in the columnar layout!");
+            }
+
+            for row in 0..operand_rows {
+                block.push(
+                    operand_strings
+                        .iter()
+                        .rev()
+                        .map(|operand_string| operand_string.chars().nth(row).unwrap_or(' '))
+                        .collect(),
+                );
+            }
+
+            width
         };
 
+        // Each operator is left-aligned on its own line after the operands
+        for row in 0..operator_rows {
+            let operator_string = operator_strings.get(row).map_or("", String::as_str);
+
+            block.push(format!("{operator_string:width$}"));
+        }
+
+        Ok(block)
+    }
+
+    /// Lay the problems out as a worksheet which parse_from_file will read back as the same problems,
+    /// using the naive row layout or the vertical-digit columnar layout
+    ///
+    /// An Err is returned if the problems cannot be laid out that way,
+    /// e.g. problems with different numbers of operands in the naive layout.
+    fn render_worksheet(problems: &[Problem], naive: bool) -> Result<String, &'static str> {
+        // Every block needs the same number of lines, so problems with fewer digits are padded.
+        // The naive layout cannot be padded, since a line of spaces would be parsed as an operand.
+        let operand_rows = problems
+            .iter()
+            .map(|problem| problem.operand_rows(naive))
+            .max()
+            .unwrap_or(0);
+
+        if naive
+            && problems
+                .iter()
+                .any(|problem| problem.operand_rows(naive) != operand_rows)
+        {
+            return Err("Every problem needs the same number of operands in the naive layout!");
+        }
+
+        // A problem with an infix expression has its operators on several lines
+        let operator_rows = problems
+            .iter()
+            .map(|problem| problem.operator_strings().len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![String::new(); operand_rows + operator_rows];
+
+        for (index, problem) in problems.iter().enumerate() {
+            let block = problem.render_block(naive, operand_rows, operator_rows)?;
+
+            // Problems are separated by a column of spaces
+            for (line, block_line) in lines.iter_mut().zip(block) {
+                if index != 0 {
+                    line.push(' ');
+                }
+                line.push_str(&block_line);
+            }
+        }
+
+        let mut worksheet = String::new();
+
+        if !problems.is_empty() {
+            for line in lines {
+                worksheet.push_str(&line);
+                worksheet.push('\n');
+            }
+        }
+
+        Ok(worksheet)
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut operands_iter = self.operands.iter();
 
         let formatter_width = f.width();
@@ -145,8 +807,8 @@ impl Display for Problem {
             };
 
             // The remaining operators are formatted the same, except precedeed by the operator
-            for operand in operands_iter {
-                write!(f, " {operator_char} ")?;
+            for (operand, operator) in operands_iter.zip(self.operators_between()) {
+                write!(f, " {operator} ")?;
 
                 if let Some(width) = formatter_width {
                     write!(f, "{operand:width$}")?;
@@ -160,27 +822,314 @@ impl Display for Problem {
     }
 }
 
+/// Evaluate and print each problem, followed by the grand total of their answers
+///
+/// Evaluation stops at the first problem which fails, or when the grand total overflows,
+/// and an Err is returned with the index of that problem.
+fn print_answers<N: Number>(problems: &[Problem]) -> Result<(), TotalError> {
+    let mut grand_total = N::from(0);
+
+    for (index, problem) in problems.iter().enumerate() {
+        let answer: N = problem
+            .evaluate()
+            .map_err(|error| TotalError::Problem(index, error))?;
+
+        grand_total = N::apply(Operator::Add, grand_total, answer.clone())
+            .map_err(|_| TotalError::GrandTotalOverflow(index))?;
+
+        println!("{answer:ANSWER_FORMAT_WIDTH$} = {problem:OPERAND_FORMAT_WIDTH$}");
+    }
+
+    println!("{} +", "-".repeat(ANSWER_FORMAT_WIDTH));
+    println!("{grand_total:ANSWER_FORMAT_WIDTH$}");
+
+    Ok(())
+}
+
+/// Print each problem's slice of the worksheet next to the operands read from it
+/// and the resulting answer, in both the naive and columnar readings
+fn explain<N: Number>(file: &str) {
+    let file_grid = Problem::file_to_grid(file);
+
+    let readings = [
+        ("Naive", Problem::parse_from_file(file, true)),
+        ("Columnar", Problem::parse_from_file(file, false)),
+    ];
+
+    println!("Naive operands are read along each line, top-to-bottom.");
+    println!(
+        "Columnar operands are read down each column, from the rightmost column to the leftmost."
+    );
+    println!();
+
+    for (index, columns) in Problem::problem_bounds(&file_grid).into_iter().enumerate() {
+        let slice: Vec<String> = file_grid
+            .iter()
+            .map(|row| row[columns.clone()].iter().collect())
+            .collect();
+
+        let notes: Vec<String> = readings
+            .iter()
+            .map(|(name, parsed)| match parsed {
+                Ok(problems) => {
+                    let problem = &problems[index];
+
+                    match problem.evaluate::<N>() {
+                        Ok(answer) => format!("{name:8}: {answer} = {problem}"),
+                        Err(error) => format!("{name:8}: {error} in {problem}"),
+                    }
+                }
+                Err(error) => format!("{name:8}: {error}"),
+            })
+            .collect();
+
+        println!(
+            "Problem {index} (columns {} to {}):",
+            columns.start + 1,
+            columns.end
+        );
+
+        // Show the slice and the notes side by side, line by line
+        for line in 0..slice.len().max(notes.len()) {
+            let slice_line = slice
+                .get(line)
+                .map_or(" ".repeat(columns.len()), String::clone);
+            let note = notes.get(line).map_or("", String::as_str);
+
+            println!("{}", format!("  {slice_line} | {note}").trim_end());
+        }
+
+        println!();
+    }
+}
+
+/// The options which can be given on the command line
+struct Options {
+    // Whether to compute the answers exactly however large they get
+    big: bool,
+    // If set, the layout to print the worksheet in, rather than solving it.
+    // true for the naive layout, false for the columnar layout.
+    render_naive: Option<bool>,
+    // Whether to explain how each problem was read, rather than solving the worksheet
+    explain: bool,
+}
+
+impl Options {
+    /// Build the options from command line arguments
+    ///
+    /// --big computes the answers exactly however large they get.
+    /// --render <naive|columnar> prints the problems, as read naively, in the given layout.
+    /// --explain shows how each problem was read in both readings.
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, &'static str> {
+        let mut options = Options {
+            big: false,
+            render_naive: None,
+            explain: false,
+        };
+
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--big" => options.big = true,
+                "--explain" => options.explain = true,
+                "--render" => {
+                    options.render_naive = match args.next().as_deref() {
+                        Some("naive") => Some(true),
+                        Some("columnar") => Some(false),
+                        _ => return Err("--render should be followed by naive or columnar!"),
+                    }
+                }
+                _ => return Err("Unrecognised argument!"),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
 fn main() {
     let file = std::fs::read_to_string(INPUT_PATH)
         .expect("INPUT_PATH should contain the path of the input file");
 
-    for (title, problems) in [
+    let options = Options::from_args(std::env::args().skip(1)).unwrap();
+
+    if let Some(naive) = options.render_naive {
+        match Problem::parse_from_file(&file, true) {
+            Ok(problems) => match Problem::render_worksheet(&problems, naive) {
+                Ok(worksheet) => print!("{worksheet}"),
+                Err(error) => println!("{error}"),
+            },
+            Err(error) => println!("{error}"),
+        }
+
+        return;
+    }
+
+    if options.explain {
+        if options.big {
+            explain::<BigUint>(&file);
+        } else {
+            explain::<u64>(&file);
+        }
+
+        return;
+    }
+
+    for (title, parsed) in [
         ("Naive Approach:", Problem::parse_from_file(&file, true)),
         ("Correct Approach:", Problem::parse_from_file(&file, false)),
     ] {
         println!("{title}");
 
-        let mut grand_total = 0;
+        // A worksheet laid out for one reading may not make sense in the other
+        let problems = match parsed {
+            Ok(problems) => problems,
+            Err(error) => {
+                println!("{error}");
+                println!();
+                continue;
+            }
+        };
 
-        for problem in &problems {
-            let answer = problem.evaluate();
-            grand_total += answer;
+        let result = if options.big {
+            print_answers::<BigUint>(&problems)
+        } else {
+            print_answers::<u64>(&problems)
+        };
 
-            println!("{answer:ANSWER_FORMAT_WIDTH$} = {problem:OPERAND_FORMAT_WIDTH$}");
+        if let Err(error) = result {
+            println!("{error}");
+
+            if !options.big && error.is_overflow() {
+                println!("Run with --big to compute exact answers.");
+            }
         }
 
-        println!("{} +", "-".repeat(ANSWER_FORMAT_WIDTH));
-        println!("{grand_total:ANSWER_FORMAT_WIDTH$}");
         println!();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPERATORS: [Operator; 8] = [
+        Operator::Multiply,
+        Operator::Add,
+        Operator::Subtract,
+        Operator::Divide,
+        Operator::Remainder,
+        Operator::Power,
+        Operator::Min,
+        Operator::Max,
+    ];
+
+    /// Check the problems are parsed back exactly as they were rendered in the given layout
+    fn assert_round_trip(problems: &[Problem], naive: bool) {
+        let worksheet = Problem::render_worksheet(problems, naive)
+            .expect("The problems should be renderable in this layout");
+
+        match Problem::parse_from_file(&worksheet, naive) {
+            Ok(parsed) => assert_eq!(parsed, problems, "Worksheet:\n{worksheet}"),
+            Err(error) => panic!("{error}\nWorksheet:\n{worksheet}"),
+        }
+    }
+
+    #[test]
+    fn single_operators_round_trip() {
+        for naive in [true, false] {
+            let problems: Vec<Problem> = OPERATORS
+                .iter()
+                .enumerate()
+                .map(|(index, &operator)| Problem {
+                    operands: vec![123 + index as u64, 45, 6, 7890],
+                    operators: Operators::Single(operator),
+                })
+                .collect();
+
+            assert_round_trip(&problems, naive);
+        }
+    }
+
+    #[test]
+    fn infix_problems_round_trip() {
+        let problems = vec![
+            Problem {
+                operands: vec![3, 400, 5],
+                operators: Operators::Infix(vec![Operator::Add, Operator::Multiply]),
+            },
+            Problem {
+                operands: vec![98, 7, 10],
+                operators: Operators::Infix(vec![Operator::Min, Operator::Max]),
+            },
+            Problem {
+                operands: vec![2, 3, 2],
+                operators: Operators::Single(Operator::Max),
+            },
+            Problem {
+                operands: vec![1000, 20, 3],
+                operators: Operators::Infix(vec![Operator::Power, Operator::Remainder]),
+            },
+        ];
+
+        for naive in [true, false] {
+            assert_round_trip(&problems, naive);
+        }
+    }
+
+    #[test]
+    fn columnar_layout_pads_shorter_problems() {
+        let problems = vec![
+            Problem {
+                operands: vec![1, 22, 333],
+                operators: Operators::Single(Operator::Min),
+            },
+            Problem {
+                operands: vec![4, 5],
+                operators: Operators::Single(Operator::Add),
+            },
+            Problem {
+                operands: vec![6, 7, 8, 9],
+                operators: Operators::Infix(vec![
+                    Operator::Subtract,
+                    Operator::Divide,
+                    Operator::Max,
+                ]),
+            },
+        ];
+
+        assert_round_trip(&problems, false);
+    }
+
+    #[test]
+    fn example_round_trips() {
+        let file = std::fs::read_to_string("example").expect("The example should be readable");
+
+        for naive in [true, false] {
+            let problems = Problem::parse_from_file(&file, naive)
+                .expect("The example should parse in both readings");
+
+            assert_round_trip(&problems, naive);
+        }
+    }
+
+    #[test]
+    fn unrenderable_problems_are_errors() {
+        let uneven = [
+            Problem {
+                operands: vec![1, 2],
+                operators: Operators::Single(Operator::Add),
+            },
+            Problem {
+                operands: vec![3],
+                operators: Operators::Single(Operator::Add),
+            },
+        ];
+        assert!(Problem::render_worksheet(&uneven, true).is_err());
+
+        let narrow = [Problem {
+            operands: vec![12, 34],
+            operators: Operators::Single(Operator::Min),
+        }];
+        assert!(Problem::render_worksheet(&narrow, false).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use std::{fmt::Write, path::Path};
+
+use crate::{PathCount, QuantumGridTile};
+
+// The colours the heat map runs through, from the fewest paths to the most
+const GRADIENT: [[u8; 3]; 4] = [[40, 60, 200], [40, 200, 200], [240, 220, 40], [220, 40, 30]];
+
+// The number of blocks drawn in the key below the heat map
+const KEY_WIDTH: usize = 16;
+
+/// The colour of a position the given fraction of the way along the gradient
+fn colour(fraction: f64) -> [u8; 3] {
+    let scaled = fraction.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f64;
+    let index = (scaled as usize).min(GRADIENT.len() - 2);
+    let remainder = scaled - index as f64;
+
+    let [low, high] = [GRADIENT[index], GRADIENT[index + 1]];
+    std::array::from_fn(|channel| {
+        (low[channel] as f64 + (high[channel] as f64 - low[channel] as f64) * remainder) as u8
+    })
+}
+
+/// The colour of a Superposition whose value needs this many bits, on a log scale up to max_bits
+fn log_colour(bits: usize, max_bits: usize) -> [u8; 3] {
+    if max_bits <= 1 {
+        return colour(0.0);
+    }
+
+    colour((bits.saturating_sub(1)) as f64 / (max_bits - 1) as f64)
+}
+
+/// Print the grid with each beam coloured by how many paths reach it, followed by a key
+///
+/// The colours are log-scaled, since the number of paths doubles at every splitter.
+/// Tiles without a beam are drawn in grey.
+/// The key is left out when the counts are not exact, since a remainder has no scale to show.
+pub fn print_ansi<V: PathCount>(quantum_grid: &[Vec<QuantumGridTile<V>>]) {
+    let max_bits = quantum_grid
+        .iter()
+        .flatten()
+        .filter_map(|tile| match tile {
+            QuantumGridTile::Superposition(value) => Some(value.bits()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut output = String::new();
+
+    for row in quantum_grid {
+        for tile in row {
+            match tile {
+                QuantumGridTile::Superposition(value) => {
+                    let [r, g, b] = log_colour(value.bits(), max_bits);
+                    write!(output, "\x1b[38;2;{r};{g};{b}m{tile}")
+                }
+                _ => write!(output, "\x1b[90m{tile}"),
+            }
+            .expect("Writing to a String should not fail");
+        }
+        output.push_str("\x1b[0m\n");
+    }
+
+    if V::EXACT {
+        output.push_str("Paths: 1 ");
+        for block in 0..KEY_WIDTH {
+            let [r, g, b] = colour(block as f64 / (KEY_WIDTH - 1) as f64);
+            write!(output, "\x1b[38;2;{r};{g};{b}m█").expect("Writing to a String should not fail");
+        }
+        writeln!(output, "\x1b[0m up to 2^{max_bits}")
+            .expect("Writing to a String should not fail");
+    }
+
+    print!("{output}");
+}
+
+/// Write the number of paths reaching each position of the grid to the given path as a CSV matrix
+///
+/// Positions without a beam are left empty,
+/// so they stay distinct from beams whose count is 0 modulo something.
+pub fn write_csv<V: PathCount>(
+    quantum_grid: &[Vec<QuantumGridTile<V>>],
+    path: &Path,
+) -> std::io::Result<()> {
+    let mut csv = String::new();
+
+    for row in quantum_grid {
+        let values: Vec<String> = row
+            .iter()
+            .map(|tile| match tile {
+                QuantumGridTile::Superposition(value) => value.to_string(),
+                _ => String::new(),
+            })
+            .collect();
+
+        csv.push_str(&values.join(","));
+        csv.push('\n');
+    }
+
+    std::fs::write(path, csv)
+}
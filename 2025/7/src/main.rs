@@ -1,147 +1,821 @@
+mod heat_map;
+mod paths;
+
 use std::{
     fmt::Display,
+    io::BufRead,
     ops::{Add, AddAssign},
+    path::PathBuf,
 };
 
+use common::BigUint;
+use paths::{BeamPaths, ExactCount, Rng};
+
 const EXAMPLE: bool = false;
 const INPUT_PATH: &str = if EXAMPLE { "example" } else { "input" };
 const START_CHAR: char = 'S';
 const SPLITTER_CHAR: char = '^';
 const BEAM_CHAR: char = '|';
 const EMPTY_CHAR: char = '.';
+const LEFT_DEFLECTOR_CHAR: char = '<';
+const RIGHT_DEFLECTOR_CHAR: char = '>';
+const ABSORBER_CHAR: char = '#';
+const LEFT_SPLITTER_CHAR: char = '[';
+const RIGHT_SPLITTER_CHAR: char = ']';
+const TRIPLE_SPLITTER_CHAR: char = '*';
 
-#[derive(Clone, Copy)]
-enum QuantumGridTile {
+/// The number types which counts of paths (the values of Superpositions) can be kept in
+///
+/// A count of 0 does not mean there is no beam, since a count modulo p can be 0 however many paths
+/// there are, so whether a beam is present is always tracked separately from its count.
+trait PathCount: Clone + Display {
+    /// Whether the count is the exact number of paths, rather than only its remainder modulo something
+    const EXACT: bool = true;
+
+    /// Add two counts together, or None if the result does not fit
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /// The number of bits needed to write the count, which places it on a log scale
+    fn bits(&self) -> usize;
+
+    /// Add another count onto this one.
+    ///
+    /// Panics if the result does not fit
+    fn accumulate(&mut self, other: &Self) {
+        *self = self
+            .checked_add(other)
+            .expect("The path count should fit in the chosen --count type");
+    }
+}
+
+impl PathCount for u64 {
+    fn checked_add(&self, other: &u64) -> Option<u64> {
+        u64::checked_add(*self, *other)
+    }
+
+    fn bits(&self) -> usize {
+        (u64::BITS - self.leading_zeros()) as usize
+    }
+}
+
+impl PathCount for u128 {
+    fn checked_add(&self, other: &u128) -> Option<u128> {
+        u128::checked_add(*self, *other)
+    }
+
+    fn bits(&self) -> usize {
+        (u128::BITS - self.leading_zeros()) as usize
+    }
+}
+
+impl PathCount for BigUint {
+    fn checked_add(&self, other: &BigUint) -> Option<BigUint> {
+        Some(self + other)
+    }
+
+    fn bits(&self) -> usize {
+        BigUint::bits(self)
+    }
+}
+
+/// A count of paths modulo some number, which never overflows however many paths there are
+#[derive(Clone)]
+struct Modular {
+    value: u64,
+    modulus: u64,
+}
+
+impl Modular {
+    /// Create the count of the value modulo the (non-zero) modulus
+    fn new(value: u64, modulus: u64) -> Self {
+        Modular {
+            value: value % modulus,
+            modulus,
+        }
+    }
+}
+
+impl Display for Modular {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl PathCount for Modular {
+    const EXACT: bool = false;
+
+    fn checked_add(&self, other: &Modular) -> Option<Modular> {
+        // Both values are below the modulus, so their sum fits in a u128
+        let sum = u128::from(self.value) + u128::from(other.value);
+
+        Some(Modular {
+            value: (sum % u128::from(self.modulus)) as u64,
+            modulus: self.modulus,
+        })
+    }
+
+    fn bits(&self) -> usize {
+        self.value.bits()
+    }
+}
+
+/// Add a count onto the count in the slot, or put it there if the slot is empty.
+///
+/// Panics if the result does not fit
+fn accumulate_into<V: PathCount>(slot: &mut Option<V>, value: &V) {
+    match slot {
+        Some(total) => total.accumulate(value),
+        None => *slot = Some(value.clone()),
+    }
+}
+
+/// The total of the counts, or None if there are none to add up
+fn total<'a, V: PathCount + 'a>(values: impl IntoIterator<Item = &'a V>) -> Option<V> {
+    let mut total = None;
+
+    for value in values {
+        accumulate_into(&mut total, value);
+    }
+
+    total
+}
+
+/// Display a total which may have nothing in it, in which case it is 0
+fn display_total<V: PathCount>(total: &Option<V>) -> String {
+    total
+        .as_ref()
+        .map_or_else(|| String::from("0"), V::to_string)
+}
+
+#[derive(Clone)]
+enum QuantumGridTile<V> {
     Splitter,
     Empty,
-    Superposition(u64),
+    Superposition(V),
+    /// Sends a beam falling onto it off to the left
+    LeftDeflector,
+    /// Sends a beam falling onto it off to the right
+    RightDeflector,
+    /// Swallows any beam that reaches it
+    Absorber,
+    /// Splits a beam falling onto it to the left, while the rest continues down through it
+    LeftSplitter,
+    /// Splits a beam falling onto it to the right, while the rest continues down through it
+    RightSplitter,
+    /// Splits a beam falling onto it to the left, to the right, and straight down through it
+    TripleSplitter,
+}
+use QuantumGridTile::{
+    Absorber, Empty, LeftDeflector, LeftSplitter, RightDeflector, RightSplitter, Splitter,
+    Superposition, TripleSplitter,
+};
+
+/// A way for a beam to leave a tile it has fallen onto
+#[derive(Clone, Copy)]
+enum Exit {
+    Left,
+    Right,
+    Down,
+}
+use Exit::{Down, Left, Right};
+
+impl Exit {
+    /// The column a beam leaving a tile in the given column this way moves to,
+    /// or None if it would leave the side of a row of the given width.
+    fn step(self, col: usize, width: usize) -> Option<usize> {
+        match self {
+            Left => col.checked_sub(1),
+            Right => Some(col + 1).filter(|&col| col < width),
+            Down => Some(col),
+        }
+    }
+
+    /// The opposite sideways direction.
+    fn reversed(self) -> Self {
+        match self {
+            Left => Right,
+            Right => Left,
+            Down => Down,
+        }
+    }
+}
+
+/// What happens to a beam moving sideways off the edge of the grid
+#[derive(Clone, Copy)]
+enum EdgePolicy {
+    /// The beam leaves the grid and is counted as lost
+    Lost,
+    /// The beam bounces off the edge, moving back the way it came
+    Reflected,
+    /// The beam comes back in on the opposite side of the row
+    Wrapped,
+}
+
+impl TryFrom<&str> for EdgePolicy {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "lost" => Ok(EdgePolicy::Lost),
+            "reflect" => Ok(EdgePolicy::Reflected),
+            "wrap" => Ok(EdgePolicy::Wrapped),
+            _ => Err("Edge policy should be one of lost, reflect or wrap!"),
+        }
+    }
+}
+
+impl<V> QuantumGridTile<V> {
+    /// Every way a beam falling onto this tile leaves it.
+    ///
+    /// Beams leaving Left or Right move sideways along the row,
+    /// while beams leaving Down fall into the row below.
+    fn exits(&self) -> &'static [Exit] {
+        match self {
+            Empty | Superposition(_) => &[Down],
+            Absorber => &[],
+            LeftDeflector => &[Left],
+            RightDeflector => &[Right],
+            Splitter => &[Left, Right],
+            LeftSplitter => &[Left, Down],
+            RightSplitter => &[Right, Down],
+            TripleSplitter => &[Left, Down, Right],
+        }
+    }
+
+    /// Whether a beam can come to rest on this tile, forming a Superposition.
+    fn is_open(&self) -> bool {
+        matches!(self, Empty | Superposition(_))
+    }
 }
-use QuantumGridTile::{Empty, Splitter, Superposition};
 
 // Display a QuantumGridTile, using a single character for each possibility.
 //
 // The value attached to any Superposition is not displayed.
-impl Display for QuantumGridTile {
+impl<V> Display for QuantumGridTile<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Determine the corrresponding char, then use the char implementation of Display
         match self {
             Empty => EMPTY_CHAR,
             Superposition(_) => BEAM_CHAR,
             Splitter => SPLITTER_CHAR,
+            LeftDeflector => LEFT_DEFLECTOR_CHAR,
+            RightDeflector => RIGHT_DEFLECTOR_CHAR,
+            Absorber => ABSORBER_CHAR,
+            LeftSplitter => LEFT_SPLITTER_CHAR,
+            RightSplitter => RIGHT_SPLITTER_CHAR,
+            TripleSplitter => TRIPLE_SPLITTER_CHAR,
         }
         .fmt(f)
     }
 }
 
-impl TryFrom<char> for QuantumGridTile {
-    type Error = &'static str;
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
+impl<V: PathCount> QuantumGridTile<V> {
+    /// Parse a tile from its character, where a source starts with a beam of the given count.
+    fn parse(value: char, source: &V) -> Result<Self, &'static str> {
         match value {
             EMPTY_CHAR => Ok(Empty),
-            START_CHAR => Ok(Superposition(1)),
+            START_CHAR => Ok(Superposition(source.clone())),
             SPLITTER_CHAR => Ok(Splitter),
-            _ => Err("Only .S^ are supported!"),
+            LEFT_DEFLECTOR_CHAR => Ok(LeftDeflector),
+            RIGHT_DEFLECTOR_CHAR => Ok(RightDeflector),
+            ABSORBER_CHAR => Ok(Absorber),
+            LEFT_SPLITTER_CHAR => Ok(LeftSplitter),
+            RIGHT_SPLITTER_CHAR => Ok(RightSplitter),
+            TRIPLE_SPLITTER_CHAR => Ok(TripleSplitter),
+            _ => Err("Only .S^<>#[]* are supported!"),
         }
     }
 }
 
 // Add together two QuantumGridTiles
 //
-// The sum of two QuantumGridTiles is not necessarily defined,
-// as a Splitter cannot be added.
+// The sum of two QuantumGridTiles is only defined when both are open,
+// as a Splitter (or any other obstacle) cannot be added.
 //
-// Superposition(a) + Superposition(b) becomes Superposition(a+b).
+// Superposition(a) + Superposition(b) becomes Superposition(a+b),
+// provided a+b fits in the value type.
 // Empty is treated as Superposition(0).
-impl Add for QuantumGridTile {
+impl<V: PathCount> Add for QuantumGridTile<V> {
     type Output = Result<Self, &'static str>;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let error = Err("Cannot add a Splitter!");
-
-        match self {
-            Splitter => error,
-            Empty => match rhs {
-                Splitter => error,
-                _ => Ok(rhs),
-            },
-            Superposition(lhs_value) => match rhs {
-                Splitter => error,
-                Empty => Ok(self),
-                Superposition(rhs_value) => Ok(Superposition(lhs_value + rhs_value)),
-            },
+        match (self, rhs) {
+            (Empty, rhs @ (Empty | Superposition(_))) => Ok(rhs),
+            (lhs @ Superposition(_), Empty) => Ok(lhs),
+            (Superposition(lhs_value), Superposition(rhs_value)) => lhs_value
+                .checked_add(&rhs_value)
+                .map(Superposition)
+                .ok_or("Superposition value overflowed!"),
+            _ => Err("Cannot add a Splitter or other obstacle!"),
         }
     }
 }
 
 /// Add two QuantumGridTiles and assign the result to the former.
 ///
-/// Panics if one of the tiles is a Splitter
-impl AddAssign for QuantumGridTile {
+/// Panics if one of the tiles is a Splitter or other obstacle, or if the value overflows
+impl<V: PathCount> AddAssign for QuantumGridTile<V> {
     fn add_assign(&mut self, rhs: Self) {
-        *self = (*self + rhs).unwrap();
+        let lhs = std::mem::replace(self, Empty);
+
+        *self = (lhs + rhs).unwrap();
     }
 }
 
-fn main() {
-    let file = std::fs::read_to_string(INPUT_PATH)
-        .expect("INPUT_PATH should contain the path of the input file");
+/// Where a beam leaving a tile ends up
+#[derive(Clone, Copy)]
+enum Landing {
+    /// The beam falls into the row below from the given column
+    At(usize),
+    /// The beam was swallowed by an Absorber
+    Absorbed,
+    /// The beam left the side of the grid, or is trapped moving along the row forever
+    Lost,
+}
+
+/// Find where a beam leaving the tile in the given column of a row ends up.
+///
+/// Beams leaving Down fall straight into the row below.
+/// Beams moving sideways slide over any Splitters or deflectors in their way until they come to rest,
+/// and are swallowed if they reach an Absorber.
+/// What happens at the edges of the row depends on the EdgePolicy.
+fn landing<V>(
+    row: &[QuantumGridTile<V>],
+    mut col: usize,
+    exit: Exit,
+    edges: EdgePolicy,
+) -> Landing {
+    if matches!(exit, Down) {
+        return Landing::At(col);
+    }
+
+    let width = row.len();
+    let mut direction = exit;
+
+    // A beam which crosses the whole row twice without coming to rest is trapped
+    // bouncing or wrapping around forever, so it is lost as well
+    for _ in 0..2 * width {
+        col = match (direction.step(col, width), edges) {
+            (Some(col), _) => col,
+            (None, EdgePolicy::Lost) => return Landing::Lost,
+            (None, EdgePolicy::Reflected) => {
+                // Turn around in place, so the beam moves off the edge tile again on the next step
+                direction = direction.reversed();
+                continue;
+            }
+            (None, EdgePolicy::Wrapped) => match direction {
+                Left => width - 1,
+                _ => 0,
+            },
+        };
+
+        if row[col].is_open() {
+            return Landing::At(col);
+        } else if matches!(row[col], Absorber) {
+            return Landing::Absorbed;
+        }
+    }
+
+    Landing::Lost
+}
+
+/// The result of sending every beam down through the grid, as far as it has been sent so far
+struct Propagation<V> {
+    /// The number of times a beam was split
+    splits: usize,
+    /// The beams falling out of the bottom of each column of the last row, if there are any
+    falling: Vec<Option<V>>,
+    /// The total value of the beams lost off the sides of the grid, if any were
+    lost: Option<V>,
+}
+
+impl<V: PathCount> Propagation<V> {
+    /// Start sending beams down through a grid of the given width, before the first row
+    fn new(grid_width: usize) -> Self {
+        Propagation {
+            splits: 0,
+            falling: vec![None; grid_width],
+            lost: None,
+        }
+    }
 
-    // Convert the file into a 2D vector of QuantumGridTiles, using the TryFrom implementation
-    let mut quantum_grid: Vec<Vec<QuantumGridTile>> = file
-        .lines()
+    /// Send the beams falling out of the last row down through the next one.
+    ///
+    /// Beams moving sideways off the edge of the grid are handled according to the EdgePolicy.
+    fn step(&mut self, row: &mut [QuantumGridTile<V>], edges: EdgePolicy) {
+        // Beams passing straight down through a tile (rather than coming to rest on it) in each column
+        let mut passing = vec![None; row.len()];
+
+        for (col, value) in self.falling.iter().enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+
+            let exits = row[col].exits();
+
+            // Keep track of how many times the beams split
+            if exits.len() > 1 {
+                self.splits += 1;
+            }
+
+            for &exit in exits {
+                match exit {
+                    Down if row[col].is_open() => row[col] += Superposition(value.clone()),
+                    Down => accumulate_into(&mut passing[col], value),
+                    _ => match landing(row, col, exit, edges) {
+                        Landing::At(rest) => row[rest] += Superposition(value.clone()),
+                        Landing::Absorbed => {}
+                        Landing::Lost => accumulate_into(&mut self.lost, value),
+                    },
+                }
+            }
+        }
+
+        // Every beam at rest in this row, as well as those passing through it, falls into the next
+        for (tile, passing) in row.iter().zip(passing.iter_mut()) {
+            if let Superposition(value) = tile {
+                accumulate_into(passing, value);
+            }
+        }
+
+        self.falling = passing;
+    }
+}
+
+/// The row and column of each source in a grid which has not been propagated yet,
+/// where the only Superpositions are the sources, in reading order
+fn sources<V>(quantum_grid: &[Vec<QuantumGridTile<V>>]) -> Vec<(usize, usize)> {
+    quantum_grid
+        .iter()
+        .enumerate()
+        .flat_map(|(row, tiles)| {
+            tiles
+                .iter()
+                .enumerate()
+                .filter(|(_, tile)| matches!(tile, Superposition(_)))
+                .map(move |(col, _)| (row, col))
+        })
+        .collect()
+}
+
+/// Send the beams from each of the given sources down through the grid on their own,
+/// ignoring every other source.
+///
+/// Splitters hit by beams from several sources count as an activation for each of them,
+/// so the splits from every source may add up to more than when they are sent down together.
+fn propagate_each_source<V: PathCount>(
+    quantum_grid: &[Vec<QuantumGridTile<V>>],
+    sources: &[(usize, usize)],
+    edges: EdgePolicy,
+) -> Vec<((usize, usize), Propagation<V>)> {
+    sources
+        .iter()
+        .map(|&source| {
+            let mut isolated_grid = quantum_grid.to_vec();
+
+            for &(row, col) in sources {
+                if (row, col) != source {
+                    isolated_grid[row][col] = Empty;
+                }
+            }
+
+            (source, propagate(&mut isolated_grid, edges))
+        })
+        .collect()
+}
+
+/// Send every beam in the grid down through it, row by row.
+///
+/// Beams moving sideways off the edge of the grid are handled according to the EdgePolicy.
+fn propagate<V: PathCount>(
+    quantum_grid: &mut [Vec<QuantumGridTile<V>>],
+    edges: EdgePolicy,
+) -> Propagation<V> {
+    let mut propagation = Propagation::new(quantum_grid[0].len());
+
+    for row in quantum_grid.iter_mut() {
+        propagation.step(row, edges);
+    }
+
+    propagation
+}
+
+/// Send every beam down through a grid read line by line from the reader,
+/// where each source starts with a beam of the given count.
+///
+/// Only the current row and the beams falling into it are kept, rather than the whole grid,
+/// so the memory needed does not grow with the height of the grid.
+fn propagate_stream<V: PathCount>(
+    mut reader: impl BufRead,
+    edges: EdgePolicy,
+    source: &V,
+) -> Result<Propagation<V>, &'static str> {
+    let mut line = String::new();
+    let mut row = vec![];
+    let mut propagation = None;
+
+    while reader
+        .read_line(&mut line)
+        .map_err(|_| "Could not read a line of the grid!")?
+        > 0
+    {
+        row.clear();
+        for value in line.trim_end_matches(['\n', '\r']).chars() {
+            row.push(QuantumGridTile::parse(value, source)?);
+        }
+        line.clear();
+
+        // The first row decides the width of the grid
+        let propagation = propagation.get_or_insert_with(|| Propagation::new(row.len()));
+
+        if row.len() != propagation.falling.len() {
+            return Err("Every row of the grid should be the same width!");
+        }
+
+        propagation.step(&mut row, edges);
+    }
+
+    propagation.ok_or("The grid should not be empty!")
+}
+
+struct Options {
+    // What happens to beams moving sideways off the edge of the grid
+    edges: EdgePolicy,
+    // The number type to count paths with
+    count_type: String,
+    // If set, count paths modulo this instead
+    modulus: Option<u64>,
+    // Whether to colour the printed grid by how many paths reach each beam
+    heat_map: bool,
+    // The file to write the number of paths reaching each position to as CSV, if any
+    csv_path: Option<PathBuf>,
+    // If set, which beam paths to print instead of counting universes
+    path_query: Option<PathQuery>,
+    // The seed for sampling paths at random, if not taken from the clock
+    seed: Option<u64>,
+    // Whether to read the grid from stdin one row at a time, without keeping or printing it
+    stream: bool,
+}
+
+/// Which of the beam paths to print
+enum PathQuery {
+    /// Every path, in order
+    All,
+    /// The path with the given index, which is parsed once the count type is known
+    Nth(String),
+    /// This many paths, picked uniformly at random
+    Sample(usize),
+}
+
+impl Options {
+    /// Build the options from command line arguments
+    ///
+    /// --edges <lost|reflect|wrap> chooses what happens to beams moving off the side of the grid,
+    /// defaulting to lost.
+    /// --count <u64|u128|big> chooses the number type used to count paths, defaulting to u64.
+    /// --modulo <p> counts paths modulo p instead.
+    /// --heat-map colours the printed grid by how many paths reach each beam,
+    /// and --csv <path> writes those counts to a CSV file.
+    /// --paths prints every path from a source to the bottom of the grid, --path <k> prints the
+    /// k-th (counting from 0), and --sample <n> prints n at random, optionally with --seed <seed>.
+    /// --stream reads the grid from stdin one row at a time, so very tall grids fit in memory.
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Options, &'static str> {
+        let mut options = Options {
+            edges: EdgePolicy::Lost,
+            count_type: String::from("u64"),
+            modulus: None,
+            heat_map: false,
+            csv_path: None,
+            path_query: None,
+            seed: None,
+            stream: false,
+        };
+
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--heat-map" => options.heat_map = true,
+                "--paths" => options.path_query = Some(PathQuery::All),
+                "--stream" => options.stream = true,
+                "--path" => {
+                    options.path_query = Some(PathQuery::Nth(
+                        args.next()
+                            .ok_or("--path should be followed by an index!")?,
+                    ))
+                }
+                "--sample" => {
+                    options.path_query = Some(PathQuery::Sample(
+                        args.next()
+                            .and_then(|samples| samples.parse().ok())
+                            .ok_or("--sample should be followed by a number of paths!")?,
+                    ))
+                }
+                "--seed" => {
+                    options.seed = Some(
+                        args.next()
+                            .and_then(|seed| seed.parse().ok())
+                            .ok_or("--seed should be followed by a number!")?,
+                    )
+                }
+                "--csv" => {
+                    options.csv_path = Some(PathBuf::from(
+                        args.next().ok_or("--csv should be followed by a path!")?,
+                    ))
+                }
+                "--edges" => {
+                    options.edges = EdgePolicy::try_from(
+                        args.next()
+                            .as_deref()
+                            .ok_or("--edges should be followed by an edge policy!")?,
+                    )?
+                }
+                "--count" => {
+                    options.count_type = args
+                        .next()
+                        .ok_or("--count should be followed by a number type!")?
+                }
+                "--modulo" => {
+                    let modulus = args
+                        .next()
+                        .and_then(|modulus| modulus.parse().ok())
+                        .ok_or("--modulo should be followed by a number!")?;
+
+                    if modulus == 0 {
+                        return Err("Cannot count modulo 0!");
+                    }
+
+                    options.modulus = Some(modulus);
+                }
+                _ => return Err("Unrecognised argument!"),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Convert the file into a 2D vector of QuantumGridTiles, where each source starts with a beam of the given count
+fn parse_grid<V: PathCount>(file: &str, source: &V) -> Vec<Vec<QuantumGridTile<V>>> {
+    file.lines()
         .map(|line| {
             line.chars()
-                .map(|value| QuantumGridTile::try_from(value).unwrap())
+                .map(|value| QuantumGridTile::parse(value, source).unwrap())
                 .collect()
         })
-        .collect();
-
-    let grid_height = quantum_grid.len();
-    let grid_width = quantum_grid[0].len();
-
-    let mut splits = 0;
-
-    // For every row except the last
-    for row in 0..grid_height - 1 {
-        for col in 0..grid_width {
-            let here = quantum_grid[row][col];
-
-            // Only need to modify the grid when a tachyon beam (Superposition) is encountered
-            if matches!(here, Superposition(_)) {
-                if matches!(quantum_grid[row + 1][col], Splitter) {
-                    // When a Splitter is encountered, the entire superposition here doubles
-                    // and splits in two, going left and right in the row below
-                    quantum_grid[row + 1][col - 1] += here;
-                    quantum_grid[row + 1][col + 1] += here;
-
-                    // Keep track of how many times the beams split
-                    splits += 1;
-                } else {
-                    // When no splitter is encountered, the beam simply travels down
-                    quantum_grid[row + 1][col] += here;
+        .collect()
+}
+
+/// Print the beam paths through the grid in the input file chosen by the query, using V to count paths.
+fn print_paths<V: ExactCount>(query: &PathQuery, options: &Options) {
+    let file = std::fs::read_to_string(INPUT_PATH)
+        .expect("INPUT_PATH should contain the path of the input file");
+
+    let quantum_grid = parse_grid(&file, &V::from(1));
+    let beam_paths = BeamPaths::new(&quantum_grid, options.edges);
+
+    match query {
+        PathQuery::All => {
+            for path in beam_paths.iter() {
+                println!("{path}");
+            }
+        }
+        PathQuery::Nth(index) => {
+            let index: V = index
+                .parse()
+                .ok()
+                .expect("The path index should be a number");
+
+            match beam_paths.nth(&index) {
+                Some(path) => println!("{path}"),
+                None => println!("There are only {} paths!", beam_paths.count()),
+            }
+        }
+        PathQuery::Sample(samples) => {
+            // Without a seed, use the clock so each run picks different paths
+            let seed = options.seed.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .expect("The clock should be after 1970")
+                    .as_nanos() as u64
+            });
+            let mut rng = Rng::new(seed);
+
+            for _ in 0..*samples {
+                match beam_paths.sample(&mut rng) {
+                    Some(path) => println!("{path}"),
+                    None => println!("There are no paths to sample!"),
                 }
             }
         }
     }
+}
 
-    // Add up all the Superposition values in the final row of the grid
-    let parallel_universes = quantum_grid
-        .last()
-        .expect("Should be a last row of the grid")
-        .iter()
-        .filter_map(|tile| match tile {
-            Superposition(value) => Some(value),
-            _ => None,
-        })
-        .sum::<u64>();
+/// Print how many times the beams split, and how many universes they formed or lost
+fn report<V: PathCount>(propagation: &Propagation<V>) {
+    let Propagation {
+        splits,
+        falling,
+        lost,
+    } = propagation;
+
+    // Every beam falling out of the bottom of the grid is a parallel universe
+    let parallel_universes = display_total(&total(falling.iter().flatten()));
+
+    println!("There beam splits {splits} times, forming {parallel_universes} parallel universes.");
+
+    if let Some(lost) = lost {
+        println!("A further {lost} universes were lost off the sides of the grid.");
+    }
+}
+
+/// Count the universes formed by the grid in the input file (or stdin), using V to count paths,
+/// where each source starts with a beam of the given count.
+fn run<V: PathCount>(options: &Options, source: V) {
+    if options.stream {
+        let propagation =
+            propagate_stream(std::io::stdin().lock(), options.edges, &source).unwrap();
+
+        return report(&propagation);
+    }
+
+    let file = std::fs::read_to_string(INPUT_PATH)
+        .expect("INPUT_PATH should contain the path of the input file");
 
-    for row in quantum_grid {
-        for value in row {
-            print!("{value}");
+    let mut quantum_grid = parse_grid(&file, &source);
+
+    // Work out what each source contributes before the grid is filled with beams from all of them.
+    // The total is only broken down when more than one source contributed to it.
+    let sources = sources(&quantum_grid);
+    let source_propagations = if sources.len() > 1 {
+        propagate_each_source(&quantum_grid, &sources, options.edges)
+    } else {
+        vec![]
+    };
+
+    let propagation = propagate(&mut quantum_grid, options.edges);
+
+    if let Some(csv_path) = &options.csv_path {
+        heat_map::write_csv(&quantum_grid, csv_path).expect("Should be able to write the CSV file");
+    }
+
+    if options.heat_map {
+        heat_map::print_ansi(&quantum_grid);
+    } else {
+        for row in quantum_grid {
+            for value in row {
+                print!("{value}");
+            }
+            println!();
         }
-        println!();
     }
 
-    println!("There beam splits {splits} times, forming {parallel_universes} parallel universes.");
+    report(&propagation);
+
+    for ((row, col), propagation) in source_propagations {
+        let universes = display_total(&total(propagation.falling.iter().flatten()));
+
+        let reached: Vec<String> = propagation
+            .falling
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_some())
+            .map(|(col, _)| col.to_string())
+            .collect();
+
+        println!(
+            "The source at row {row}, column {col} forms {universes} universes ({} lost) \
+             with {} splitter activations, reaching the bottom in columns [{}].",
+            display_total(&propagation.lost),
+            propagation.splits,
+            reached.join(", ")
+        );
+    }
+}
+
+fn main() {
+    let options = Options::from_args(std::env::args().skip(1)).unwrap();
+
+    if let Some(query) = &options.path_query {
+        assert!(
+            options.modulus.is_none(),
+            "Paths can only be picked out by index when they are counted exactly"
+        );
+
+        return match options.count_type.as_str() {
+            "u64" => print_paths::<u64>(query, &options),
+            "u128" => print_paths::<u128>(query, &options),
+            "big" => print_paths::<BigUint>(query, &options),
+            _ => panic!("The count type should be one of u64, u128 or big"),
+        };
+    }
+
+    if let Some(modulus) = options.modulus {
+        return run(&options, Modular::new(1, modulus));
+    }
+
+    match options.count_type.as_str() {
+        "u64" => run(&options, 1u64),
+        "u128" => run(&options, 1u128),
+        "big" => run(&options, BigUint::from(1u64)),
+        _ => panic!("The count type should be one of u64, u128 or big"),
+    }
 }
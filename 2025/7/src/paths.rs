@@ -0,0 +1,267 @@
+use std::{fmt::Display, str::FromStr};
+
+use common::BigUint;
+
+use crate::{EdgePolicy, Exit, Landing, PathCount, QuantumGridTile, landing, sources};
+
+/// The path counts which are exact, so that paths can be picked out by their index
+pub trait ExactCount: PathCount + Ord + FromStr + From<u64> {
+    /// Subtract another count from this one, or None if it is larger
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+
+    fn is_zero(&self) -> bool;
+}
+
+impl ExactCount for u64 {
+    fn checked_sub(&self, other: &u64) -> Option<u64> {
+        u64::checked_sub(*self, *other)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
+impl ExactCount for u128 {
+    fn checked_sub(&self, other: &u128) -> Option<u128> {
+        u128::checked_sub(*self, *other)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
+impl ExactCount for BigUint {
+    fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        BigUint::checked_sub(self, other)
+    }
+
+    fn is_zero(&self) -> bool {
+        BigUint::is_zero(self)
+    }
+}
+
+/// A small SplitMix64 pseudo-random number generator, for sampling paths
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly random count below the given (non-zero) bound
+    ///
+    /// Counts with as many bits as the bound are drawn until one falls below it,
+    /// which takes fewer than two attempts on average.
+    fn below<V: ExactCount>(&mut self, bound: &V) -> V {
+        let one = V::from(1);
+
+        loop {
+            let mut value = V::from(0);
+            let mut bits = 0;
+
+            // Build the count one bit at a time, using only addition
+            for _ in 0..bound.bits() {
+                if bits == 0 {
+                    bits = self.next_u64();
+                }
+
+                value.accumulate(&value.clone());
+                if bits & 1 == 1 {
+                    value.accumulate(&one);
+                }
+                bits >>= 1;
+            }
+
+            if value < *bound {
+                return value;
+            }
+        }
+    }
+}
+
+// Display an Exit as the initial of its direction
+impl Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Exit::Left => 'L',
+            Exit::Right => 'R',
+            Exit::Down => 'D',
+        }
+        .fmt(f)
+    }
+}
+
+/// One way for a beam to get from a source to the bottom of the grid
+pub struct BeamPath {
+    /// The row and column of the source the beam starts from
+    pub source: (usize, usize),
+    /// The way the beam left each tile which gave it a choice, in order from the top
+    pub decisions: Vec<Exit>,
+}
+
+// Display a BeamPath as its source followed by the initial of each decision, e.g. "(0, 7): LRRL"
+impl Display for BeamPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: ", self.source)?;
+
+        for decision in &self.decisions {
+            decision.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Every path a beam can take from a source to the bottom of the grid, in a fixed order
+///
+/// Paths from earlier sources (in reading order) come first,
+/// and paths choosing an earlier exit at a tile (in the order given by exits) come before later ones.
+/// Beams which are absorbed or lost do not reach the bottom, so their paths are not included.
+pub struct BeamPaths<'a, V> {
+    quantum_grid: &'a [Vec<QuantumGridTile<V>>],
+    edges: EdgePolicy,
+    /// The number of paths to the bottom for a beam falling onto each tile,
+    /// with an extra row of 1s at the end for beams which have already fallen out of the grid
+    ways: Vec<Vec<V>>,
+    /// The row and column of each source, in reading order
+    sources: Vec<(usize, usize)>,
+}
+
+impl<'a, V: ExactCount> BeamPaths<'a, V> {
+    /// Count the paths from every tile of a grid which has not been propagated yet,
+    /// so that its only Superpositions are the sources.
+    pub fn new(quantum_grid: &'a [Vec<QuantumGridTile<V>>], edges: EdgePolicy) -> Self {
+        let grid_width = quantum_grid.first().map_or(0, |row| row.len());
+
+        let mut beam_paths = BeamPaths {
+            quantum_grid,
+            edges,
+            ways: vec![vec![V::from(1); grid_width]],
+            sources: sources(quantum_grid),
+        };
+
+        // Work up from the bottom, since the paths from each tile continue from the row below
+        for row in (0..quantum_grid.len()).rev() {
+            let ways_below = beam_paths.ways.last().expect("Should be a row below");
+
+            let ways = (0..grid_width)
+                .map(|col| {
+                    let mut ways = V::from(0);
+                    for (_, below) in beam_paths.choices(row, col) {
+                        ways.accumulate(&ways_below[below]);
+                    }
+                    ways
+                })
+                .collect();
+
+            beam_paths.ways.push(ways);
+        }
+
+        beam_paths.ways.reverse();
+
+        beam_paths
+    }
+
+    /// Each exit from the tile at the given position which leads to the row below,
+    /// along with the column it falls into the row below from
+    fn choices(&self, row: usize, col: usize) -> impl Iterator<Item = (Exit, usize)> + '_ {
+        let tiles = &self.quantum_grid[row];
+
+        tiles[col].exits().iter().filter_map(move |&exit| {
+            match landing(tiles, col, exit, self.edges) {
+                Landing::At(below) => Some((exit, below)),
+                _ => None,
+            }
+        })
+    }
+
+    /// The total number of paths from every source
+    pub fn count(&self) -> V {
+        let mut count = V::from(0);
+
+        for &(row, col) in &self.sources {
+            count.accumulate(&self.ways[row + 1][col]);
+        }
+
+        count
+    }
+
+    /// The path with the given index, or None if there are not that many paths
+    pub fn nth(&self, index: &V) -> Option<BeamPath> {
+        let mut index = index.clone();
+
+        // Skip over the sources with too few paths to include the index
+        let &source = self.sources.iter().find(|&&(row, col)| {
+            let ways = &self.ways[row + 1][col];
+
+            match index.checked_sub(ways) {
+                Some(rest) => {
+                    index = rest;
+                    false
+                }
+                None => true,
+            }
+        })?;
+
+        let (mut row, mut col) = (source.0 + 1, source.1);
+        let mut decisions = vec![];
+
+        while row < self.quantum_grid.len() {
+            // Skip over the exits with too few paths to include the index, in the same way
+            let (exit, below) = self
+                .choices(row, col)
+                .find(
+                    |&(_, below)| match index.checked_sub(&self.ways[row + 1][below]) {
+                        Some(rest) => {
+                            index = rest;
+                            false
+                        }
+                        None => true,
+                    },
+                )
+                .expect("The index should be below the number of paths from this tile");
+
+            if self.quantum_grid[row][col].exits().len() > 1 {
+                decisions.push(exit);
+            }
+
+            (row, col) = (row + 1, below);
+        }
+
+        Some(BeamPath { source, decisions })
+    }
+
+    /// Lazily iterate over every path in order
+    pub fn iter(&self) -> impl Iterator<Item = BeamPath> + '_ {
+        let mut index = V::from(0);
+        let one = V::from(1);
+
+        std::iter::from_fn(move || {
+            let path = self.nth(&index)?;
+            index.accumulate(&one);
+            Some(path)
+        })
+    }
+
+    /// Pick one of the paths uniformly at random, or None if there are no paths
+    pub fn sample(&self, rng: &mut Rng) -> Option<BeamPath> {
+        let count = self.count();
+
+        if count.is_zero() {
+            return None;
+        }
+
+        self.nth(&rng.below(&count))
+    }
+}
@@ -0,0 +1,107 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::{Point3D, coordinate::Coordinate, kd_tree::KdTree};
+
+// The number of nearest neighbours first looked up for each box, which doubles whenever they run out
+const INITIAL_NEIGHBOURS: usize = 8;
+
+/// A pair of distinct junction boxes, referred to by their indices in the list
+///
+/// Pairs are ordered by the (squared) distance between the boxes, and then by their indices,
+/// so that pairs at the same distance are always taken in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BoxPair<D> {
+    pub squared_distance: D,
+    /// The index of the box which comes first in the list
+    pub low: usize,
+    /// The index of the box which comes later in the list
+    pub high: usize,
+}
+
+impl<D> BoxPair<D> {
+    pub fn new<T: Coordinate<Squared = D>>(
+        junction_boxes: &[Point3D<T>],
+        a: usize,
+        b: usize,
+    ) -> Self {
+        BoxPair {
+            squared_distance: junction_boxes[a].squared_distance(&junction_boxes[b]),
+            low: a.min(b),
+            high: a.max(b),
+        }
+    }
+}
+
+/// Every pair of distinct boxes, closest first, found lazily as they are needed
+///
+/// Each box keeps a list of its nearest neighbours from a k-d tree, and a min-heap holds
+/// the closest pair from each box's list not yet taken. The closest pair overall is always
+/// on top of the heap, so only as many pairs as are taken ever need to be found.
+///
+/// Every pair is in the lists of both of its boxes, so it is only taken from its low box's list.
+pub struct ClosestPairs<'a, T: Coordinate> {
+    junction_boxes: &'a [Point3D<T>],
+    tree: KdTree<'a, T>,
+    /// The nearest neighbours of each box looked up so far, closest first
+    neighbours: Vec<Vec<usize>>,
+    /// The number of neighbours from each box's list which have been put on the heap
+    taken: Vec<usize>,
+    /// The next pair from each box's list, along with the box it came from
+    heap: BinaryHeap<Reverse<(BoxPair<T::Squared>, usize)>>,
+}
+
+impl<'a, T: Coordinate> ClosestPairs<'a, T> {
+    pub fn new(junction_boxes: &'a [Point3D<T>]) -> Self {
+        let mut closest_pairs = ClosestPairs {
+            junction_boxes,
+            tree: KdTree::new(junction_boxes),
+            neighbours: vec![vec![]; junction_boxes.len()],
+            taken: vec![0; junction_boxes.len()],
+            heap: BinaryHeap::new(),
+        };
+
+        for from in 0..junction_boxes.len() {
+            closest_pairs.push_next(from);
+        }
+
+        closest_pairs
+    }
+
+    /// Put the next pair from the box's list on the heap, looking up more neighbours if needed
+    fn push_next(&mut self, from: usize) {
+        let others = self.junction_boxes.len() - 1;
+        let neighbours = &mut self.neighbours[from];
+
+        if self.taken[from] == neighbours.len() {
+            // Every other box has already been paired with this one
+            if neighbours.len() == others {
+                return;
+            }
+
+            let k = (2 * neighbours.len()).max(INITIAL_NEIGHBOURS).min(others);
+            *neighbours = self.tree.nearest(from, k);
+        }
+
+        let to = neighbours[self.taken[from]];
+        self.taken[from] += 1;
+
+        self.heap
+            .push(Reverse((BoxPair::new(self.junction_boxes, from, to), from)));
+    }
+}
+
+impl<T: Coordinate> Iterator for ClosestPairs<'_, T> {
+    type Item = BoxPair<T::Squared>;
+
+    fn next(&mut self) -> Option<BoxPair<T::Squared>> {
+        loop {
+            let Reverse((pair, from)) = self.heap.pop()?;
+
+            self.push_next(from);
+
+            if from == pair.low {
+                return Some(pair);
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+/// A squared distance between points with f64 coordinates
+///
+/// These are compared with f64::total_cmp so that they can be sorted,
+/// which agrees with partial_cmp for the distances between any valid points.
+#[derive(Debug, Clone, Copy)]
+pub struct TotalF64(f64);
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for TotalF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for TotalF64 {}
+
+/// The number types which the coordinates of a Point3D can be stored in
+pub trait Coordinate: Copy + Display + FromStr {
+    /// The type squared distances are calculated in, which must be totally ordered
+    type Squared: Copy + Ord + Debug;
+    /// The type the product of two coordinates is calculated in
+    type Product: Display;
+
+    /// The square of the difference between two coordinates
+    fn squared_difference(self, other: Self) -> Self::Squared;
+
+    /// Add up the squared differences along each axis
+    fn sum(squares: [Self::Squared; 3]) -> Self::Squared;
+
+    fn compare(self, other: Self) -> Ordering;
+
+    fn product(self, other: Self) -> Self::Product;
+}
+
+// Integer coordinates are used exactly, so distances are never rounded.
+// Differences between i32s fit in an i64, so their squares (and sums of 3 squares) always fit in an i128.
+impl Coordinate for i32 {
+    type Squared = i128;
+    type Product = i64;
+
+    fn squared_difference(self, other: i32) -> i128 {
+        let difference = (self as i64 - other as i64) as i128;
+        difference * difference
+    }
+
+    fn sum(squares: [i128; 3]) -> i128 {
+        squares.iter().sum()
+    }
+
+    fn compare(self, other: i32) -> Ordering {
+        self.cmp(&other)
+    }
+
+    fn product(self, other: i32) -> i64 {
+        self as i64 * other as i64
+    }
+}
+
+// Float coordinates may be rounded, so distances which should differ can compare equal
+impl Coordinate for f64 {
+    type Squared = TotalF64;
+    type Product = f64;
+
+    fn squared_difference(self, other: f64) -> TotalF64 {
+        TotalF64((self - other).powi(2))
+    }
+
+    fn sum(squares: [TotalF64; 3]) -> TotalF64 {
+        TotalF64(squares[0].0 + squares[1].0 + squares[2].0)
+    }
+
+    fn compare(self, other: f64) -> Ordering {
+        self.total_cmp(&other)
+    }
+
+    fn product(self, other: f64) -> f64 {
+        self * other
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::BinaryHeap;
+
+use crate::{Point3D, closest_pairs::BoxPair, coordinate::Coordinate};
+
+// The number of dimensions points are split along
+const DIMENSIONS: usize = 3;
+
+/// A k-d tree over a list of points, for finding the nearest neighbours of each point
+///
+/// The tree is stored implicitly in a list of point indices:
+/// the point at the middle of any range of the list splits the rest of that range in two,
+/// along an axis which cycles through x, y and z with the depth of the range.
+pub struct KdTree<'a, T> {
+    points: &'a [Point3D<T>],
+    order: Vec<usize>,
+}
+
+impl<'a, T: Coordinate> KdTree<'a, T> {
+    pub fn new(points: &'a [Point3D<T>]) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+
+        Self::build(points, &mut order, 0);
+
+        KdTree { points, order }
+    }
+
+    /// Arrange the range so its middle point splits the rest along the axis for this depth, recursively
+    fn build(points: &[Point3D<T>], order: &mut [usize], depth: usize) {
+        if order.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % DIMENSIONS;
+        let middle = order.len() / 2;
+
+        order.select_nth_unstable_by(middle, |&a, &b| {
+            points[a]
+                .coordinate(axis)
+                .compare(points[b].coordinate(axis))
+        });
+
+        let (before, after) = order.split_at_mut(middle);
+        Self::build(points, before, depth + 1);
+        Self::build(points, &mut after[1..], depth + 1);
+    }
+
+    /// The (up to) k other points closest to the point with the given index, closest first
+    ///
+    /// Points at the same distance are ordered as their BoxPairs would be,
+    /// so the k nearest are always the first k of any larger number of nearest.
+    pub fn nearest(&self, target: usize, k: usize) -> Vec<usize> {
+        // The best pairs found so far, with the worst of them on top
+        let mut best = BinaryHeap::with_capacity(k + 1);
+
+        if k > 0 {
+            self.search(&self.order, 0, target, k, &mut best);
+        }
+
+        best.into_sorted_vec()
+            .into_iter()
+            .map(|pair: BoxPair<T::Squared>| {
+                if pair.low == target {
+                    pair.high
+                } else {
+                    pair.low
+                }
+            })
+            .collect()
+    }
+
+    fn search(
+        &self,
+        order: &[usize],
+        depth: usize,
+        target: usize,
+        k: usize,
+        best: &mut BinaryHeap<BoxPair<T::Squared>>,
+    ) {
+        if order.is_empty() {
+            return;
+        }
+
+        let axis = depth % DIMENSIONS;
+        let middle = order.len() / 2;
+        let splitter = order[middle];
+
+        if splitter != target {
+            best.push(BoxPair::new(self.points, target, splitter));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+
+        // Search the side of the split containing the target first, since it is more likely to be close
+        let target_coordinate = self.points[target].coordinate(axis);
+        let splitter_coordinate = self.points[splitter].coordinate(axis);
+        let (near, far) = if target_coordinate.compare(splitter_coordinate).is_lt() {
+            (&order[..middle], &order[middle + 1..])
+        } else {
+            (&order[middle + 1..], &order[..middle])
+        };
+
+        self.search(near, depth + 1, target, k, best);
+
+        // The far side can only hold a closer point (or one just as close) if the split is close enough.
+        // Ties must still be searched, since they may come first by index.
+        let squared_offset = target_coordinate.squared_difference(splitter_coordinate);
+        let worst = best.peek().map(|pair| pair.squared_distance);
+        if best.len() < k || worst.is_some_and(|worst| squared_offset <= worst) {
+            self.search(far, depth + 1, target, k, best);
+        }
+    }
+}
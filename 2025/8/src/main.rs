@@ -1,17 +1,27 @@
+mod closest_pairs;
+mod coordinate;
+mod kd_tree;
+
+use std::fmt::Display;
+
+use closest_pairs::{BoxPair, ClosestPairs};
+use common::DisjointSet;
+use coordinate::Coordinate;
+
 const EXAMPLE: bool = false;
 const INPUT_PATH: &str = if EXAMPLE { "example" } else { "input" };
 const PART_1_WIRES: usize = if EXAMPLE { 10 } else { 1000 };
 
 /// Cartesian coordinate representation of a point in 3D space
 #[derive(Debug, Clone, Copy, PartialEq)]
-struct Point3D {
-    x: f64,
-    y: f64,
-    z: f64,
+struct Point3D<T> {
+    x: T,
+    y: T,
+    z: T,
 }
 
-impl Point3D {
-    /// Given one string of the form "x,y,z", where x, y and z are floats, construct a Point3D
+impl<T: Coordinate> Point3D<T> {
+    /// Given one string of the form "x,y,z", where x, y and z are coordinates of type T, construct a Point3D
     ///
     /// The constructed Point3D object will be returned wrapped by Ok.
     /// An Err will be returned if the format is incorrect
@@ -43,14 +53,82 @@ impl Point3D {
         Ok(points)
     }
 
-    /// Calculate the straight-line distance between two points in 3D space using Pythagoras
-    fn distance(&self, other: &Self) -> f64 {
-        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2))
-            .sqrt()
+    /// Calculate the square of the straight-line distance between two points in 3D space using Pythagoras
+    ///
+    /// This orders pairs of points the same way as the distance itself, without taking a square root.
+    fn squared_distance(&self, other: &Self) -> T::Squared {
+        T::sum([
+            self.x.squared_difference(other.x),
+            self.y.squared_difference(other.y),
+            self.z.squared_difference(other.z),
+        ])
+    }
+
+    /// The coordinate along the given axis, where 0, 1 and 2 are x, y and z
+    fn coordinate(&self, axis: usize) -> T {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
+}
+
+/// Every pair of distinct boxes, closest first, found by sorting the whole list of pairs
+///
+/// This takes O(n^2) memory, so it is only used with --exhaustive and to check the ClosestPairs against.
+fn all_pairs_sorted<T: Coordinate>(junction_boxes: &[Point3D<T>]) -> Vec<BoxPair<T::Squared>> {
+    // Create a list of all unique pairs of distinct boxes, ignoring order
+    // That is, all ways to choose 2 boxes from the list, referring to each box by its index
+    let mut box_pairs = vec![];
+
+    // box_b will always come before box_a in the list
+    // so skip the first value when picking box_a
+    for a in 1..junction_boxes.len() {
+        // Limit the values of box_b to come before box_a in the list
+        // This prevents including the same pair in both orders
+        for b in 0..a {
+            box_pairs.push(BoxPair::new(junction_boxes, a, b));
+        }
+    }
+
+    // Sort with the smallest distances first
+    box_pairs.sort_unstable();
+
+    box_pairs
+}
+
+struct Options {
+    // Whether to sort every pair of boxes, rather than finding the closest pairs as they are needed
+    exhaustive: bool,
+    // Whether to read the coordinates as floats, rather than exactly as integers
+    float: bool,
+}
+
+impl Options {
+    /// Build the options from command line arguments
+    ///
+    /// --exhaustive sorts every pair of boxes up front, instead of using a k-d tree.
+    /// --float reads the coordinates as floats, which may round distances, instead of integers.
+    fn from_args(args: impl Iterator<Item = String>) -> Result<Options, &'static str> {
+        let mut options = Options {
+            exhaustive: false,
+            float: false,
+        };
+
+        for flag in args {
+            match flag.as_str() {
+                "--exhaustive" => options.exhaustive = true,
+                "--float" => options.float = true,
+                _ => return Err("Unrecognised argument!"),
+            }
+        }
+
+        Ok(options)
     }
 }
 
-impl std::fmt::Display for Point3D {
+impl<T: Display> std::fmt::Display for Point3D<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "(x=")?;
         self.x.fmt(f)?;
@@ -62,85 +140,40 @@ impl std::fmt::Display for Point3D {
     }
 }
 
-fn main() {
-    let file = std::fs::read_to_string(INPUT_PATH)
-        .expect("INPUT_PATH should contain the path of the input file");
-
-    let junction_boxes = Point3D::parse_many_from_csv(&file).unwrap();
-
-    // Create a list of all unique pairs of distinct boxes, ignoring order
-    // That is, all ways to choose 2 boxes from the list
-    let mut box_pairs: Vec<(Point3D, Point3D)> = vec![];
+/// Connect up the junction boxes in the file, with coordinates of type T
+fn run<T: Coordinate>(file: &str, options: &Options) {
+    let junction_boxes = Point3D::<T>::parse_many_from_csv(file).unwrap();
 
-    // box_b will always come before box_a in the list
-    // so skip the first value when picking box_a
-    for (i, box_a) in junction_boxes.iter().enumerate().skip(1) {
-        // Limit the values of box_b to come before box_a in the list
-        // This prevents including the same pair in both orders
-        for box_b in &junction_boxes[..i] {
-            box_pairs.push((*box_a, *box_b));
-        }
-    }
+    // The pairs of boxes, closest first, either all found up front or lazily as they are needed
+    let mut box_pairs: Box<dyn Iterator<Item = BoxPair<T::Squared>>> = if options.exhaustive {
+        Box::new(all_pairs_sorted(&junction_boxes).into_iter())
+    } else {
+        Box::new(ClosestPairs::new(&junction_boxes))
+    };
 
-    // Sort by comparing the distances between pairs of boxes, with the smallest distances first
-    // Need to jump through some hoops to sort by f64 in Rust, since f64 does not implement Ord
-    box_pairs.sort_unstable_by(|(box_1a, box_1b), (box_2a, box_2b)| {
-        box_2a
-            .distance(box_2b)
-            .partial_cmp(&box_1a.distance(box_1b))
-            .expect("The distances should all be comparable")
-    });
-
-    // Initialize the list of circuits such that each box is alone in its own circuit
-    let mut circuits: Vec<Vec<Point3D>> = junction_boxes
-        .iter()
-        .map(|&junction_box| vec![junction_box])
-        .collect();
+    // Initialize the circuits such that each box is alone in its own circuit
+    let mut circuits = DisjointSet::new(junction_boxes.len());
 
     let mut wires = 0;
 
     // Keep going until all the junction boxes are linked up into one big circuit
-    while circuits.len() > 1 {
-        // Pop the closest pair of boxes we have not yet checked
-        let (box_a, box_b) = box_pairs
-            .pop()
-            .expect("Should be distances left in the list");
+    while circuits.set_count() > 1 {
+        // Take the closest pair of boxes we have not yet checked
+        let BoxPair {
+            high: a, low: b, ..
+        } = box_pairs
+            .next()
+            .expect("Should be pairs of boxes left to check");
 
-        // Add a wire between box_a and box_b
+        // Add a wire between box_a and box_b,
+        // merging their circuits if they were not previously in the same circuit
         wires += 1;
-
-        // We need to index a's circuit instead of getting it directly with .find(),
-        // to prevent multiple mutable references being active for circuits
-        let mut a_circuit_index = circuits
-            .iter_mut()
-            .position(|circuit| circuit.contains(&box_a))
-            .expect("Some circuit should contain box_a");
-
-        // We only need to merge circuits if box_a and and box_b
-        // were not previously in the same circuit
-        if !circuits[a_circuit_index].contains(&box_b) {
-            let b_circuit_index = circuits
-                .iter_mut()
-                .position(|circuit| circuit.contains(&box_b))
-                .expect("Some circuit should contain box_b");
-
-            // b's circuit is removed
-            let mut b_circuit = circuits.remove(b_circuit_index);
-
-            // We must update a's circuit's index if it was affected by b's circuit's removal
-            if b_circuit_index < a_circuit_index {
-                a_circuit_index -= 1;
-            }
-
-            // b's circuit mergees into a's circuit
-            circuits[a_circuit_index].append(&mut b_circuit);
-        }
+        circuits.union(a, b);
 
         // Problem 1 is concerned with the state of the circuits after adding some number of wires
         if wires == PART_1_WIRES {
             // Find out how many junction boxes are in each circuit and sort in decscending order
-            let mut circuit_sizes: Vec<usize> =
-                circuits.iter().map(|circuit| circuit.len()).collect();
+            let mut circuit_sizes = circuits.set_sizes();
             circuit_sizes.sort_unstable();
             circuit_sizes.reverse();
 
@@ -159,11 +192,13 @@ fn main() {
         }
 
         // Problem 2 is concerned with the last two junction boxes connected to form one big circuit
-        if circuits.len() == 1 {
+        if circuits.set_count() == 1 {
+            let (box_a, box_b) = (junction_boxes[a], junction_boxes[b]);
+
             println!("{box_a} and {box_b} were the last two junction boxes connected.");
             println!(
                 "The product of their x coordinates is {}.",
-                (box_a.x * box_b.x) as u64
+                box_a.x.product(box_b.x)
             );
         }
 
@@ -171,3 +206,97 @@ fn main() {
         // for finding the minimum spanning tree of a graph
     }
 }
+
+fn main() {
+    let file = std::fs::read_to_string(INPUT_PATH)
+        .expect("INPUT_PATH should contain the path of the input file");
+
+    let options = Options::from_args(std::env::args().skip(1)).unwrap();
+
+    if options.float {
+        run::<f64>(&file, &options);
+    } else {
+        run::<i32>(&file, &options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift generator, so the random cases are the same on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// A random coordinate from the given number of values either side of the offset
+        fn coordinate(&mut self, spread: i64, offset: i64) -> i32 {
+            (offset + (self.next() % (2 * spread as u64 + 1)) as i64 - spread) as i32
+        }
+    }
+
+    /// Random boxes packed into a small space, so many are duplicates or at equal distances
+    fn random_boxes(rng: &mut Rng, spread: i64, offset: i64) -> Vec<Point3D<i32>> {
+        (0..rng.next() % 60)
+            .map(|_| Point3D {
+                x: rng.coordinate(spread, offset),
+                y: rng.coordinate(spread, offset),
+                z: rng.coordinate(spread, offset),
+            })
+            .collect()
+    }
+
+    fn assert_matches_exhaustive<T: Coordinate>(junction_boxes: &[Point3D<T>]) {
+        let lazy: Vec<_> = ClosestPairs::new(junction_boxes).collect();
+
+        assert_eq!(lazy, all_pairs_sorted(junction_boxes));
+    }
+
+    #[test]
+    fn integer_pairs_match_exhaustive() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for trial in 0..300 {
+            // Tightly packed boxes, then boxes spread towards the extremes of i32
+            let junction_boxes = match trial % 3 {
+                0 => random_boxes(&mut rng, 2, 0),
+                1 => random_boxes(&mut rng, 40, -1000),
+                _ => random_boxes(&mut rng, i32::MAX as i64, 0),
+            };
+
+            assert_matches_exhaustive(&junction_boxes);
+        }
+    }
+
+    #[test]
+    fn float_pairs_match_exhaustive() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..300 {
+            // Quarters are exact in an f64, so distances which should tie really do
+            let junction_boxes: Vec<Point3D<f64>> = random_boxes(&mut rng, 8, 0)
+                .into_iter()
+                .map(|point| Point3D {
+                    x: point.x as f64 / 4.0,
+                    y: point.y as f64 / 4.0,
+                    z: point.z as f64 / 4.0,
+                })
+                .collect();
+
+            assert_matches_exhaustive(&junction_boxes);
+        }
+    }
+
+    #[test]
+    fn duplicate_boxes_match_exhaustive() {
+        let junction_boxes = vec![Point3D { x: 5, y: -3, z: 7 }; 20];
+
+        assert_matches_exhaustive(&junction_boxes);
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, AddAssign, Mul},
+    str::FromStr,
+};
+
+// Each digit of a BigUint is a base 2^32 limb
+const LIMB_BITS: usize = 32;
+
+// The largest power of 10 which fits in a limb, used to convert to decimal 9 digits at a time
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+/// An arbitrary-precision unsigned integer
+///
+/// The value is stored as base 2^32 limbs, least significant first,
+/// with no trailing zero limbs so that each value has exactly one representation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint(Vec<u32>);
+
+impl BigUint {
+    /// Create a BigUint with the value 0
+    pub fn zero() -> BigUint {
+        BigUint(vec![])
+    }
+
+    /// Returns true if the value is 0
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Remove any zero limbs from the most significant end
+    fn normalise(mut self) -> BigUint {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+
+        self
+    }
+
+    /// The number of bits needed to write the value, which is 0 for the value 0
+    pub fn bits(&self) -> usize {
+        match self.0.last() {
+            Some(last) => self.0.len() * LIMB_BITS - last.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    /// Returns true if the given bit, counting from the least significant, is set
+    fn bit(&self, index: usize) -> bool {
+        self.0
+            .get(index / LIMB_BITS)
+            .is_some_and(|limb| limb >> (index % LIMB_BITS) & 1 == 1)
+    }
+
+    /// Convert to a u32, if the value is small enough
+    pub fn to_u32(&self) -> Option<u32> {
+        match self.0.as_slice() {
+            [] => Some(0),
+            [limb] => Some(*limb),
+            _ => None,
+        }
+    }
+
+    /// Subtract other from self, returning None if the result would be negative
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if *self < *other {
+            return None;
+        }
+
+        let mut difference = Vec::with_capacity(self.0.len());
+        let mut borrow = 0i64;
+
+        for (index, &limb) in self.0.iter().enumerate() {
+            let mut limb_difference =
+                limb as i64 - *other.0.get(index).unwrap_or(&0) as i64 - borrow;
+
+            borrow = if limb_difference < 0 {
+                limb_difference += 1 << LIMB_BITS;
+                1
+            } else {
+                0
+            };
+
+            difference.push(limb_difference as u32);
+        }
+
+        Some(BigUint(difference).normalise())
+    }
+
+    /// Divide by a single limb, returning the quotient and the remainder
+    fn div_rem_limb(&self, divisor: u32) -> (BigUint, u32) {
+        let mut quotient = vec![0; self.0.len()];
+        let mut remainder = 0u64;
+
+        for (index, &limb) in self.0.iter().enumerate().rev() {
+            let dividend = (remainder << LIMB_BITS) | limb as u64;
+
+            quotient[index] = (dividend / divisor as u64) as u32;
+            remainder = dividend % divisor as u64;
+        }
+
+        (BigUint(quotient).normalise(), remainder as u32)
+    }
+
+    /// Divide self by other, returning the quotient and the remainder,
+    /// or None if other is 0
+    pub fn div_rem(&self, other: &BigUint) -> Option<(BigUint, BigUint)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        // Dividing by a single limb is common, and much faster than the general case
+        if let Some(divisor) = other.to_u32() {
+            let (quotient, remainder) = self.div_rem_limb(divisor);
+
+            return Some((quotient, BigUint::from(remainder as u64)));
+        }
+
+        // Otherwise use binary long division, bringing down one bit of self at a time
+        let mut quotient = vec![0u32; self.0.len()];
+        let mut remainder = BigUint::zero();
+
+        for index in (0..self.bits()).rev() {
+            remainder = remainder.shifted_left_one(self.bit(index));
+
+            if remainder >= *other {
+                remainder = remainder
+                    .checked_sub(other)
+                    .expect("The remainder should be at least the divisor");
+                quotient[index / LIMB_BITS] |= 1 << (index % LIMB_BITS);
+            }
+        }
+
+        Some((BigUint(quotient).normalise(), remainder))
+    }
+
+    /// Multiply by 2 and add the given bit
+    fn shifted_left_one(mut self, low_bit: bool) -> BigUint {
+        let mut carry = low_bit as u32;
+
+        for limb in &mut self.0 {
+            let next_carry = *limb >> (LIMB_BITS - 1);
+            *limb = (*limb << 1) | carry;
+            carry = next_carry;
+        }
+
+        if carry != 0 {
+            self.0.push(carry);
+        }
+
+        self
+    }
+
+    /// Raise self to the power of the exponent, using repeated squaring
+    pub fn pow(&self, mut exponent: u32) -> BigUint {
+        let mut result = BigUint::from(1u64);
+        let mut base = self.clone();
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+
+            exponent >>= 1;
+
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+
+        result
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        BigUint(vec![value as u32, (value >> LIMB_BITS) as u32]).normalise()
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(value: u128) -> Self {
+        BigUint(
+            (0..4)
+                .map(|index| (value >> (index * LIMB_BITS)) as u32)
+                .collect(),
+        )
+        .normalise()
+    }
+}
+
+// Parse a BigUint from a string of decimal digits
+impl FromStr for BigUint {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err("A BigUint should only contain decimal digits!");
+        }
+
+        let mut value = BigUint::zero();
+
+        // Work through the digits in the same chunks used by Display, most significant first,
+        // where only the first chunk may be shorter
+        let first_chunk = match s.len() % DECIMAL_CHUNK_DIGITS {
+            0 => DECIMAL_CHUNK_DIGITS,
+            len => len,
+        };
+        let mut start = 0;
+        let mut end = first_chunk;
+
+        while start < s.len() {
+            let chunk: u64 = s[start..end]
+                .parse()
+                .expect("Chunk should only contain digits");
+            let scale = 10u64.pow((end - start) as u32);
+
+            value = &(&value * &BigUint::from(scale)) + &BigUint::from(chunk);
+
+            start = end;
+            end += DECIMAL_CHUNK_DIGITS;
+        }
+
+        Ok(value)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Without trailing zero limbs, a longer value is always larger.
+        // Otherwise compare limb by limb, from the most significant.
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: &BigUint) -> BigUint {
+        let mut sum = self.clone();
+        sum += rhs;
+        sum
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, rhs: &BigUint) {
+        if self.0.len() < rhs.0.len() {
+            self.0.resize(rhs.0.len(), 0);
+        }
+
+        let mut carry = 0u64;
+
+        for (index, limb) in self.0.iter_mut().enumerate() {
+            let limb_sum = *limb as u64 + *rhs.0.get(index).unwrap_or(&0) as u64 + carry;
+
+            *limb = limb_sum as u32;
+            carry = limb_sum >> LIMB_BITS;
+
+            // Past the end of rhs, there is nothing left to do once the carry is used up
+            if carry == 0 && index >= rhs.0.len() {
+                break;
+            }
+        }
+
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+}
+
+impl Mul<&BigUint> for &BigUint {
+    type Output = BigUint;
+
+    /// Long multiplication, taking O(nm) time for values of n and m limbs
+    fn mul(self, rhs: &BigUint) -> BigUint {
+        if self.is_zero() || rhs.is_zero() {
+            return BigUint::zero();
+        }
+
+        let mut product = vec![0u32; self.0.len() + rhs.0.len()];
+
+        for (i, &lhs_limb) in self.0.iter().enumerate() {
+            let mut carry = 0u64;
+
+            for (j, &rhs_limb) in rhs.0.iter().enumerate() {
+                let limb_product =
+                    lhs_limb as u64 * rhs_limb as u64 + product[i + j] as u64 + carry;
+
+                product[i + j] = limb_product as u32;
+                carry = limb_product >> LIMB_BITS;
+            }
+
+            product[i + rhs.0.len()] = carry as u32;
+        }
+
+        BigUint(product).normalise()
+    }
+}
+
+// Display a BigUint in decimal, supporting the same width and alignment options as the primitives
+impl Display for BigUint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Split the value into chunks of decimal digits, least significant first
+        let mut chunks = vec![];
+        let mut rest = self.clone();
+
+        while !rest.is_zero() {
+            let (quotient, chunk) = rest.div_rem_limb(DECIMAL_CHUNK);
+            chunks.push(chunk);
+            rest = quotient;
+        }
+
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+
+        // Every chunk but the most significant must be padded with leading zeros
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:0DECIMAL_CHUNK_DIGITS$}"));
+        }
+
+        f.pad_integral(true, "", &digits)
+    }
+}
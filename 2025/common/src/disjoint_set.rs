@@ -0,0 +1,110 @@
+/// A partition of the elements 0..len into disjoint sets, which can be merged ("union-find")
+///
+/// Each set is a tree of elements pointing towards a root, which represents the set.
+/// Union by rank keeps the trees shallow, and path compression flattens them further
+/// as they are searched, so every operation takes nearly constant amortised time.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+    /// The next element up the tree from each element, where roots are their own parent
+    parents: Vec<usize>,
+    /// An upper bound on the height of the tree under each root
+    ranks: Vec<u8>,
+    /// The number of elements in the set of each root (only kept up to date for roots)
+    sizes: Vec<usize>,
+    /// The number of disjoint sets
+    set_count: usize,
+}
+
+impl DisjointSet {
+    /// Create a DisjointSet where each of the elements 0..len is alone in its own set
+    pub fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+            sizes: vec![1; len],
+            set_count: len,
+        }
+    }
+
+    /// The number of elements across all the sets
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    /// Returns true if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// The number of disjoint sets
+    pub fn set_count(&self) -> usize {
+        self.set_count
+    }
+
+    /// Find the root representing the set containing the element
+    ///
+    /// Every element on the way to the root is pointed directly at it, so later searches are faster.
+    /// Panics if the element is out of range.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        let mut current = element;
+        while current != root {
+            current = std::mem::replace(&mut self.parents[current], root);
+        }
+
+        root
+    }
+
+    /// Merge the sets containing the two elements
+    ///
+    /// Returns true if they were in different sets, or false if nothing needed to change.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+
+        if root_a == root_b {
+            return false;
+        }
+
+        // Hang the shallower tree under the root of the deeper one, so the height only grows on a tie
+        let (root, child) = if self.ranks[root_a] < self.ranks[root_b] {
+            (root_b, root_a)
+        } else {
+            (root_a, root_b)
+        };
+
+        if self.ranks[root] == self.ranks[child] {
+            self.ranks[root] += 1;
+        }
+
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        self.set_count -= 1;
+
+        true
+    }
+
+    /// Returns true if the two elements are in the same set
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The number of elements in the set containing the element
+    pub fn set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+
+    /// The number of elements in each set, in the order of their roots
+    pub fn set_sizes(&self) -> Vec<usize> {
+        self.parents
+            .iter()
+            .enumerate()
+            .filter(|&(element, &parent)| element == parent)
+            .map(|(root, _)| self.sizes[root])
+            .collect()
+    }
+}
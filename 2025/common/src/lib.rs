@@ -0,0 +1,9 @@
+//! Data structures shared between the 2025 solutions
+
+mod big_uint;
+mod disjoint_set;
+mod range_set;
+
+pub use big_uint::BigUint;
+pub use disjoint_set::DisjointSet;
+pub use range_set::{Integer, RangeSet};
@@ -0,0 +1,562 @@
+use std::{fmt::Debug, ops::RangeInclusive};
+
+/// The primitive integer types which can be stored in a RangeSet
+///
+/// Only types of up to 64 bits are supported, so that the number of integers in any range,
+/// even one covering the whole type, always fits in a u128.
+pub trait Integer: Copy + Ord + Debug {
+    const MIN: Self;
+    const MAX: Self;
+    const BYTES: usize;
+    const SIGNED: bool;
+
+    /// The next integer up, or None if this is the largest value of the type
+    fn successor(self) -> Option<Self>;
+
+    /// The next integer down, or None if this is the smallest value of the type
+    fn predecessor(self) -> Option<Self>;
+
+    /// The number of integers in the non-empty range start..=end
+    fn count(start: Self, end: Self) -> u128;
+
+    /// Append the little-endian bytes of the integer
+    fn write_le(self, bytes: &mut Vec<u8>);
+
+    /// Read an integer from exactly BYTES little-endian bytes
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($($type:ty),*) => {
+        $(
+            impl Integer for $type {
+                const MIN: Self = <$type>::MIN;
+                const MAX: Self = <$type>::MAX;
+                const BYTES: usize = size_of::<$type>();
+                const SIGNED: bool = <$type>::MIN != 0;
+
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn count(start: Self, end: Self) -> u128 {
+                    // Every supported type fits in an i128, so the difference cannot overflow
+                    (end as i128 - start as i128) as u128 + 1
+                }
+
+                fn write_le(self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_le(bytes: &[u8]) -> Self {
+                    <$type>::from_le_bytes(bytes.try_into().expect("Should be given exactly BYTES bytes"))
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+// The first bytes of a serialised RangeSet, identifying the format
+const MAGIC: &[u8; 4] = b"RSET";
+
+/// A set of integers, stored as sorted ranges which neither overlap nor touch
+///
+/// Keeping the ranges sorted allows membership to be checked with a binary search,
+/// and keeping them separated means each integer belongs to exactly one range.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeSet<T>(Vec<RangeInclusive<T>>);
+
+impl<T: Integer> RangeSet<T> {
+    /// Create an empty set
+    pub fn new() -> RangeSet<T> {
+        RangeSet(vec![])
+    }
+
+    /// Returns true if the set contains no integers
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the ranges making up the set, in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = &RangeInclusive<T>> {
+        self.0.iter()
+    }
+
+    /// Get the range containing the given value, if there is one
+    ///
+    /// This is a binary search, so takes O(log n) time for n ranges.
+    pub fn range_containing(&self, value: T) -> Option<&RangeInclusive<T>> {
+        // The first range which does not end before the value is the only one which could contain it
+        let index = self.0.partition_point(|range| *range.end() < value);
+
+        self.0.get(index).filter(|range| *range.start() <= value)
+    }
+
+    /// Get the range containing each of the given values, if there is one, in the same order
+    ///
+    /// Rather than searching for each value separately, the values are sorted once
+    /// and then swept through alongside the ranges, which are already sorted.
+    /// This takes O(n log n + m) time for n values and m ranges.
+    pub fn ranges_containing_all(&self, values: &[T]) -> Vec<Option<&RangeInclusive<T>>> {
+        // Sort the positions of the values rather than the values themselves,
+        // so that the results can be put back in the original order
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        order.sort_unstable_by_key(|&index| values[index]);
+
+        let mut results = vec![None; values.len()];
+        let mut ranges = self.0.iter().peekable();
+
+        for index in order {
+            let value = values[index];
+
+            // Skip past the ranges which end before this value.
+            // Since the values are ascending, no later value can be in them either.
+            while ranges.next_if(|range| *range.end() < value).is_some() {}
+
+            results[index] = ranges
+                .peek()
+                .copied()
+                .filter(|range| *range.start() <= value);
+        }
+
+        results
+    }
+
+    /// Returns true if the given value is in the set
+    pub fn contains(&self, value: T) -> bool {
+        self.range_containing(value).is_some()
+    }
+
+    /// Add every integer in the range to the set,
+    /// merging it with any ranges it overlaps or touches
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = range.into_inner();
+
+        if start > end {
+            return;
+        }
+
+        // The ranges which end more than 1 before the start are unaffected
+        let first = self.0.partition_point(|existing| {
+            existing.end().successor().is_some_and(|next| next < start)
+        });
+
+        // As are the ranges which start more than 1 after the end
+        let last = self.0.partition_point(|existing| match end.successor() {
+            Some(next) => *existing.start() <= next,
+            None => true,
+        });
+
+        // Everything in between is merged with the new range
+        if first < last {
+            start = start.min(*self.0[first].start());
+            end = end.max(*self.0[last - 1].end());
+        }
+
+        self.0.splice(first..last, [start..=end]);
+    }
+
+    /// Create the set of integers in either this set or the other
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = Vec::with_capacity(self.0.len() + other.0.len());
+        ranges.extend(self.0.iter().cloned());
+        ranges.extend(other.0.iter().cloned());
+
+        RangeSet::from_iter(ranges)
+    }
+
+    /// Create the set of integers in both this set and the other
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut ranges = vec![];
+
+        // Walk through both lists of ranges together, always advancing the one which ends first
+        let (mut i, mut j) = (0, 0);
+        while i < self.0.len() && j < other.0.len() {
+            let (a, b) = (&self.0[i], &other.0[j]);
+
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+
+            if start <= end {
+                ranges.push(start..=end);
+            }
+
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        RangeSet(ranges)
+    }
+
+    /// Create the set of integers in this set but not in the other
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        self.intersection(&other.complement(T::MIN..=T::MAX))
+    }
+
+    /// Create the set of integers within the bounds which are not in this set
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> RangeSet<T> {
+        let (bounds_start, bounds_end) = bounds.into_inner();
+        let mut ranges = vec![];
+
+        // The smallest integer which might start a gap,
+        // or None once the ranges reach the largest value of the type
+        let mut gap_start = Some(bounds_start);
+
+        for range in &self.0 {
+            let Some(start) = gap_start else {
+                break;
+            };
+
+            if start > bounds_end {
+                break;
+            }
+
+            // Ranges ending before the gap being looked for have no effect
+            if *range.end() < start {
+                continue;
+            }
+
+            // There is space before this range, so it forms a gap.
+            // The range starts after some other integer, so it must have a predecessor.
+            if *range.start() > start
+                && let Some(gap_end) = range.start().predecessor()
+            {
+                ranges.push(start..=gap_end.min(bounds_end));
+            }
+
+            gap_start = range.end().successor();
+        }
+
+        if let Some(start) = gap_start
+            && start <= bounds_end
+        {
+            ranges.push(start..=bounds_end);
+        }
+
+        RangeSet(ranges)
+    }
+
+    /// The number of integers in the set
+    ///
+    /// This is a u128 because a set covering every value of a type has one more integer
+    /// than the largest value of that type.
+    pub fn len(&self) -> u128 {
+        self.0
+            .iter()
+            .map(|range| T::count(*range.start(), *range.end()))
+            .sum()
+    }
+
+    /// Serialise the set into a compact binary format
+    ///
+    /// The format is the MAGIC bytes, then the width of the integer type in bytes
+    /// and whether it is signed (each as one byte), then the number of ranges as a little-endian u64,
+    /// and finally the start and end of each range in ascending order, as little-endian integers.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 10 + self.0.len() * 2 * T::BYTES);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(T::BYTES as u8);
+        bytes.push(T::SIGNED as u8);
+        bytes.extend_from_slice(&(self.0.len() as u64).to_le_bytes());
+
+        for range in &self.0 {
+            range.start().write_le(&mut bytes);
+            range.end().write_le(&mut bytes);
+        }
+
+        bytes
+    }
+
+    /// Deserialise a set from the binary format written by to_bytes
+    ///
+    /// An Err is returned if the bytes are not in that format, are for a different integer type,
+    /// or hold ranges which are not sorted and separated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<RangeSet<T>, &'static str> {
+        let (header, body) = bytes
+            .split_at_checked(MAGIC.len() + 10)
+            .ok_or("Too short to be a RangeSet!")?;
+
+        if &header[..MAGIC.len()] != MAGIC {
+            return Err("Not a RangeSet!");
+        }
+
+        if header[MAGIC.len()] as usize != T::BYTES || header[MAGIC.len() + 1] != T::SIGNED as u8 {
+            return Err("The RangeSet was saved with a different integer type!");
+        }
+
+        let range_count = u64::from_le_bytes(
+            header[MAGIC.len() + 2..]
+                .try_into()
+                .expect("The header should end with 8 bytes of range count"),
+        );
+
+        if range_count.checked_mul(2 * T::BYTES as u64) != Some(body.len() as u64) {
+            return Err("The number of ranges does not match the length of the RangeSet!");
+        }
+
+        let mut ranges: Vec<RangeInclusive<T>> = Vec::with_capacity(range_count as usize);
+
+        for range_bytes in body.chunks_exact(2 * T::BYTES) {
+            let (start_bytes, end_bytes) = range_bytes.split_at(T::BYTES);
+            let range = T::read_le(start_bytes)..=T::read_le(end_bytes);
+
+            if range.start() > range.end() {
+                return Err("The RangeSet contains an empty range!");
+            }
+
+            if let Some(previous) = ranges.last()
+                && previous
+                    .end()
+                    .successor()
+                    .is_none_or(|next| next >= *range.start())
+            {
+                return Err("The ranges of the RangeSet are not sorted and separated!");
+            }
+
+            ranges.push(range);
+        }
+
+        Ok(RangeSet(ranges))
+    }
+}
+
+impl<T: Integer> FromIterator<RangeInclusive<T>> for RangeSet<T> {
+    /// Create a set from any ranges, which may overlap or touch
+    ///
+    /// The ranges are sorted and then merged in a single pass, taking O(n log n) time in total.
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<RangeInclusive<T>> = iter
+            .into_iter()
+            .filter(|range| range.start() <= range.end())
+            .collect();
+        sorted.sort_unstable_by_key(|range| *range.start());
+
+        let mut ranges: Vec<RangeInclusive<T>> = vec![];
+
+        for range in sorted {
+            if let Some(last) = ranges.last_mut()
+                && last
+                    .end()
+                    .successor()
+                    .is_none_or(|next| next >= *range.start())
+            {
+                // The range overlaps or touches the previous one, so extend the previous one
+                if range.end() > last.end() {
+                    *last = *last.start()..=*range.end();
+                }
+            } else {
+                ranges.push(range);
+            }
+        }
+
+        RangeSet(ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small xorshift generator, so the random cases are the same on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    /// The naive model of a set of 8-bit integers: one bool per value, from T::MIN up
+    type Model = [bool; 256];
+
+    fn offset<T: Integer + Into<i16>>(value: T) -> usize {
+        (value.into() - T::MIN.into()) as usize
+    }
+
+    fn value<T: Integer + Into<i16> + TryFrom<i16>>(offset: usize) -> T {
+        T::try_from(T::MIN.into() + offset as i16)
+            .ok()
+            .expect("The offset should be within the type")
+    }
+
+    /// A random endpoint, picked from the edges of the type half of the time
+    fn endpoint<T: Integer + Into<i16> + TryFrom<i16>>(rng: &mut Rng) -> T {
+        match rng.next() % 8 {
+            0 => T::MIN,
+            1 => T::MIN.successor().unwrap(),
+            2 => T::MAX.predecessor().unwrap(),
+            3 => T::MAX,
+            _ => value((rng.next() % 256) as usize),
+        }
+    }
+
+    fn model_of<T: Integer + Into<i16>>(set: &RangeSet<T>) -> Model {
+        let mut model = [false; 256];
+        for range in set.iter() {
+            for covered in &mut model[offset(*range.start())..=offset(*range.end())] {
+                *covered = true;
+            }
+        }
+        model
+    }
+
+    /// Check the ranges are non-empty, sorted and separated, and hold exactly the model's values
+    fn check<T: Integer + Into<i16>>(set: &RangeSet<T>, expected: &Model) {
+        for range in set.iter() {
+            assert!(range.start() <= range.end(), "{set:?} has an empty range");
+        }
+        for (a, b) in set.iter().zip(set.iter().skip(1)) {
+            assert!(
+                offset(*a.end()) + 1 < offset(*b.start()),
+                "{set:?} has ranges which overlap or touch"
+            );
+        }
+
+        assert_eq!(&model_of(set), expected, "{set:?}");
+        assert_eq!(
+            set.len(),
+            expected.iter().filter(|&&covered| covered).count() as u128
+        );
+        assert_eq!(set.is_empty(), !expected.contains(&true));
+    }
+
+    /// A random set built by inserting random ranges, checked against the model after every insert
+    fn random_set<T: Integer + Into<i16> + TryFrom<i16>>(rng: &mut Rng) -> (RangeSet<T>, Model) {
+        let mut set = RangeSet::new();
+        let mut model = [false; 256];
+
+        for _ in 0..rng.next() % 6 {
+            let (start, end) = (endpoint::<T>(rng), endpoint::<T>(rng));
+
+            set.insert(start..=end);
+            if start <= end {
+                for covered in &mut model[offset(start)..=offset(end)] {
+                    *covered = true;
+                }
+            }
+
+            check(&set, &model);
+        }
+
+        (set, model)
+    }
+
+    fn matches_model<T: Integer + Into<i16> + TryFrom<i16>>() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..2000 {
+            let (a, model_a) = random_set::<T>(&mut rng);
+            let (b, model_b) = random_set::<T>(&mut rng);
+
+            check(
+                &a.union(&b),
+                &std::array::from_fn(|i| model_a[i] || model_b[i]),
+            );
+            check(
+                &a.intersection(&b),
+                &std::array::from_fn(|i| model_a[i] && model_b[i]),
+            );
+            check(
+                &a.difference(&b),
+                &std::array::from_fn(|i| model_a[i] && !model_b[i]),
+            );
+            check(
+                &RangeSet::from_iter(a.iter().chain(b.iter()).cloned()),
+                &std::array::from_fn(|i| model_a[i] || model_b[i]),
+            );
+
+            let (low, high) = (endpoint::<T>(&mut rng), endpoint::<T>(&mut rng));
+            check(
+                &a.complement(low..=high),
+                &std::array::from_fn(|i| !model_a[i] && offset(low) <= i && i <= offset(high)),
+            );
+
+            for (i, &covered) in model_a.iter().enumerate() {
+                assert_eq!(a.contains(value::<T>(i)), covered);
+            }
+
+            assert_eq!(RangeSet::from_bytes(&a.to_bytes()), Ok(a));
+        }
+    }
+
+    #[test]
+    fn unsigned_sets_match_model() {
+        matches_model::<u8>();
+    }
+
+    #[test]
+    fn signed_sets_match_model() {
+        matches_model::<i8>();
+    }
+
+    /// Check the set operations on the ranges at the very edges of a type
+    fn edges<T: Integer>(middle: T) {
+        let full = RangeSet::from_iter([T::MIN..=T::MAX]);
+        let empty = RangeSet::<T>::new();
+
+        assert_eq!(full.len(), 1 << (8 * T::BYTES));
+        assert_eq!(full.iter().cloned().collect::<Vec<_>>(), [T::MIN..=T::MAX]);
+
+        // Inserting the ends and then everything between them leaves a single range
+        let mut set = RangeSet::new();
+        set.insert(T::MAX..=T::MAX);
+        set.insert(T::MIN..=T::MIN);
+        assert_eq!(set.len(), 2);
+        set.insert(T::MIN.successor().unwrap()..=middle);
+        set.insert(middle..=T::MAX.predecessor().unwrap());
+        assert_eq!(set, full);
+
+        let low = RangeSet::from_iter([T::MIN..=middle]);
+        let high = RangeSet::from_iter([middle.successor().unwrap()..=T::MAX]);
+        assert_eq!(low.union(&high), full);
+        assert_eq!(low.intersection(&high), empty);
+        assert_eq!(full.intersection(&high), high);
+        assert_eq!(full.difference(&low), high);
+        assert_eq!(low.len() + high.len(), full.len());
+
+        assert_eq!(low.complement(T::MIN..=T::MAX), high);
+        assert_eq!(high.complement(T::MIN..=T::MAX), low);
+        assert_eq!(full.complement(T::MIN..=T::MAX), empty);
+        assert_eq!(empty.complement(T::MIN..=T::MAX), full);
+
+        assert_eq!(RangeSet::from_bytes(&full.to_bytes()), Ok(full));
+    }
+
+    #[test]
+    fn unsigned_edges() {
+        edges::<u8>(100);
+        edges::<u16>(1000);
+        edges::<u32>(1 << 20);
+        edges::<u64>(1 << 40);
+    }
+
+    #[test]
+    fn signed_edges() {
+        edges::<i8>(0);
+        edges::<i16>(-1000);
+        edges::<i32>(1 << 20);
+        edges::<i64>(-1 << 40);
+    }
+
+    #[test]
+    fn whole_u64_range() {
+        let mut set = RangeSet::new();
+        set.insert(0..=u64::MAX);
+
+        assert_eq!(set.len(), u64::MAX as u128 + 1);
+        assert!(set.contains(0) && set.contains(u64::MAX));
+    }
+}